@@ -0,0 +1,138 @@
+use crate::cost::Cost;
+
+/// Decides whether a sampled move replaces the current solution.
+/// Called exactly once per evaluated move.
+pub trait AcceptanceCriterion<C: Cost>: Send {
+    fn accept(&mut self, current: C, candidate: C, best: C) -> bool;
+}
+
+/// Accepts moves worsening the cost by less than a threshold that decays after every move.
+pub struct ThresholdAccepting {
+    threshold: f64,
+    decay: f64,
+}
+
+impl ThresholdAccepting {
+    pub fn new(threshold: f64, decay: f64) -> ThresholdAccepting {
+        ThresholdAccepting { threshold, decay }
+    }
+}
+
+impl<C: Cost> AcceptanceCriterion<C> for ThresholdAccepting {
+    fn accept(&mut self, current: C, candidate: C, _: C) -> bool {
+        let accept = (candidate - current).to_f64() <= self.threshold;
+        self.threshold *= self.decay;
+        accept
+    }
+}
+
+/// Accepts every solution below a water level.
+/// The level starts at the first current cost and recedes towards the best cost
+/// by `rain_speed` of the remaining gap after every move.
+pub struct GreatDeluge {
+    level: Option<f64>,
+    rain_speed: f64,
+}
+
+impl GreatDeluge {
+    pub fn new(rain_speed: f64) -> GreatDeluge {
+        GreatDeluge {
+            level: None,
+            rain_speed,
+        }
+    }
+}
+
+impl<C: Cost> AcceptanceCriterion<C> for GreatDeluge {
+    fn accept(&mut self, current: C, candidate: C, best: C) -> bool {
+        let level = self.level.get_or_insert(current.to_f64());
+        let accept = candidate <= current || candidate.to_f64() <= *level;
+        *level -= self.rain_speed * (*level - best.to_f64());
+        accept
+    }
+}
+
+/// Accepts solutions within a relative deviation from the best (record) cost.
+pub struct RecordToRecord {
+    deviation: f64,
+}
+
+impl RecordToRecord {
+    pub fn new(deviation: f64) -> RecordToRecord {
+        RecordToRecord { deviation }
+    }
+}
+
+impl<C: Cost> AcceptanceCriterion<C> for RecordToRecord {
+    fn accept(&mut self, current: C, candidate: C, best: C) -> bool {
+        candidate <= current || candidate.to_f64() <= best.to_f64() * (1.0 + self.deviation)
+    }
+}
+
+/// Late Acceptance Hill Climbing: compares the candidate with the current cost
+/// from `history_length` moves ago.
+pub struct LateAcceptance<C: Cost> {
+    history: Vec<C>,
+    history_length: usize,
+    position: usize,
+}
+
+impl<C: Cost> LateAcceptance<C> {
+    pub fn new(history_length: usize) -> LateAcceptance<C> {
+        LateAcceptance {
+            history: Vec::new(),
+            history_length: history_length.max(1),
+            position: 0,
+        }
+    }
+}
+
+impl<C: Cost> AcceptanceCriterion<C> for LateAcceptance<C> {
+    fn accept(&mut self, current: C, candidate: C, _: C) -> bool {
+        if self.history.is_empty() {
+            self.history = vec![current; self.history_length];
+        }
+        let accept = candidate <= current || candidate <= self.history[self.position];
+        self.history[self.position] = if accept { candidate } else { current };
+        self.position = (self.position + 1) % self.history_length;
+        accept
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn threshold_decays() {
+        let mut criterion = ThresholdAccepting::new(10.0, 0.5);
+        assert!(criterion.accept(100, 110, 100));
+        assert!(!criterion.accept(100, 110, 100));
+        assert!(criterion.accept(100, 102, 100));
+    }
+
+    #[test]
+    fn great_deluge_level_recedes_to_best() {
+        let mut criterion = GreatDeluge::new(0.5);
+        assert!(criterion.accept(200, 150, 100));
+        assert!(criterion.accept(150, 149, 100));
+        assert!(!criterion.accept(150, 160, 100));
+    }
+
+    #[test]
+    fn record_to_record_uses_best() {
+        let mut criterion = RecordToRecord::new(0.1);
+        assert!(criterion.accept(105, 110, 100));
+        assert!(!criterion.accept(105, 111, 100));
+    }
+
+    #[test]
+    fn late_acceptance_compares_with_history() {
+        let mut criterion = LateAcceptance::new(2);
+        assert!(criterion.accept(100, 100, 100));
+        assert!(criterion.accept(100, 90, 90));
+        // Compared with the cost from two moves ago (100), not the current one
+        assert!(criterion.accept(90, 95, 90));
+        assert!(!criterion.accept(95, 96, 90));
+    }
+}
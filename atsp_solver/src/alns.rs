@@ -0,0 +1,417 @@
+use std::cmp::Ordering;
+
+use rand::Rng;
+
+use crate::atsp::ATSP;
+use crate::cost::Cost;
+use crate::search::{Context, Explorer};
+use crate::solution::Solution;
+use crate::utils;
+
+/// Scores rewarding the operators of an iteration, as proposed by Ropke & Pisinger
+const SCORE_NEW_BEST: f64 = 33.0;
+const SCORE_IMPROVED: f64 = 9.0;
+const SCORE_ACCEPTED: f64 = 13.0;
+/// Randomization of the worst removal, higher values make it greedier
+const WORST_REMOVAL_POWER: i32 = 3;
+/// Initial temperature accepts a solution this much worse than the start with probability 0.5
+const START_WORSENING: f64 = 0.05;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DestroyOperator {
+    Random,
+    Worst,
+    Related,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum RepairOperator {
+    Cheapest,
+    Regret,
+}
+
+const DESTROY_OPERATORS: [DestroyOperator; 3] = [
+    DestroyOperator::Random,
+    DestroyOperator::Worst,
+    DestroyOperator::Related,
+];
+const REPAIR_OPERATORS: [RepairOperator; 2] = [RepairOperator::Cheapest, RepairOperator::Regret];
+
+/// Adaptive weight and usage of a destroy or repair operator.
+struct AlnsOperatorWeight {
+    weight: f64,
+    uses: u32,
+    successes: u32,
+}
+
+impl AlnsOperatorWeight {
+    fn new() -> AlnsOperatorWeight {
+        AlnsOperatorWeight {
+            weight: 1.0,
+            uses: 0,
+            successes: 0,
+        }
+    }
+
+    fn update(&mut self, score: f64, decay: f64) {
+        self.uses += 1;
+        if score > 0.0 {
+            self.successes += 1;
+        }
+        self.weight = decay * self.weight + (1.0 - decay) * score;
+    }
+}
+
+/// Roulette wheel selection proportional to the operator weights.
+fn select_operator(stats: &[AlnsOperatorWeight], rng: &mut rand::rngs::StdRng) -> usize {
+    let total: f64 = stats.iter().map(|s| s.weight).sum();
+    if total <= 0.0 {
+        return rng.gen_range(0..stats.len());
+    }
+    let mut sampled = rng.gen_range(0.0..total);
+    for (idx, s) in stats.iter().enumerate() {
+        if sampled < s.weight {
+            return idx;
+        }
+        sampled -= s.weight;
+    }
+    stats.len() - 1
+}
+
+/// Cost saved by removing the city at position `i` of a tour.
+fn removal_gain<C: Cost>(instance: &ATSP<C>, tour: &[u32], i: usize) -> C {
+    let n = tour.len();
+    let prev = tour[(i + n - 1) % n] as usize;
+    let city = tour[i] as usize;
+    let next = tour[(i + 1) % n] as usize;
+    instance.cost(prev, city) + instance.cost(city, next) - instance.cost(prev, next)
+}
+
+/// Removes the city at position `i` of the tour and returns the cost saved.
+fn remove_at<C: Cost>(
+    instance: &ATSP<C>,
+    tour: &mut Vec<u32>,
+    i: usize,
+    ctx: &mut Context<C>,
+) -> C {
+    let gain = removal_gain(instance, tour, i);
+    ctx.evaluations += 1;
+    tour.remove(i);
+    gain
+}
+
+/// Cost of inserting `city` between positions `i` and `i + 1` of a partial tour.
+fn insertion_cost<C: Cost>(instance: &ATSP<C>, tour: &[u32], city: usize, i: usize) -> C {
+    let prev = tour[i] as usize;
+    let next = tour[(i + 1) % tour.len()] as usize;
+    instance.cost(prev, city) + instance.cost(city, next) - instance.cost(prev, next)
+}
+
+/// Best and second best insertion of `city`, as (position, cost).
+fn best_insertions<C: Cost>(
+    instance: &ATSP<C>,
+    tour: &[u32],
+    city: usize,
+    ctx: &mut Context<C>,
+) -> ((usize, C), C) {
+    let mut best = (0, C::MAX);
+    let mut second = C::MAX;
+    for i in 0..tour.len() {
+        let cost = insertion_cost(instance, tour, city, i);
+        if cost < best.1 {
+            second = best.1;
+            best = (i, cost);
+        } else if cost < second {
+            second = cost;
+        }
+    }
+    ctx.evaluations += tour.len() as u32;
+    (best, second)
+}
+
+/// Adaptive large neighborhood search: repeatedly removes cities with a destroy
+/// operator and reinserts them with a repair operator, picking the operators by
+/// weights adapted from their success, and accepts the result like annealing.
+/// Cities are removed and inserted one at a time, the cost of the result adds up their
+/// gains and costs. Every gain or insertion cost computed counts as an evaluation.
+pub struct AlnsExplorer {
+    rng: rand::rngs::StdRng,
+    destroy_min: f64,
+    destroy_max: f64,
+    decay: f64,
+    cooling: f64,
+    patience: u32,
+    temperature: Option<f64>,
+    destroy_stats: Vec<AlnsOperatorWeight>,
+    repair_stats: Vec<AlnsOperatorWeight>,
+}
+
+impl AlnsExplorer {
+    pub fn new(
+        seed: u64,
+        destroy_min: f64,
+        destroy_max: f64,
+        decay: f64,
+        cooling: f64,
+        patience: u32,
+    ) -> AlnsExplorer {
+        AlnsExplorer {
+            rng: rand::SeedableRng::seed_from_u64(seed),
+            destroy_min,
+            destroy_max: destroy_max.max(destroy_min),
+            decay,
+            cooling,
+            patience,
+            temperature: None,
+            destroy_stats: DESTROY_OPERATORS
+                .iter()
+                .map(|_| AlnsOperatorWeight::new())
+                .collect(),
+            repair_stats: REPAIR_OPERATORS
+                .iter()
+                .map(|_| AlnsOperatorWeight::new())
+                .collect(),
+        }
+    }
+
+    /// Number of cities to remove, at least one and leaving at least two in the tour.
+    /// Tours of fewer than three cities have a single cycle, nothing is removed.
+    fn destroy_size(&mut self, n: usize) -> usize {
+        if n < 3 {
+            return 0;
+        }
+        let min = ((self.destroy_min * n as f64).ceil() as usize).clamp(1, n - 2);
+        let max = ((self.destroy_max * n as f64).ceil() as usize).clamp(min, n - 2);
+        self.rng.gen_range(min..=max)
+    }
+
+    /// Removes `k` cities from `tour` one at a time and returns them with the cost saved.
+    fn destroy<C: Cost>(
+        &mut self,
+        operator: DestroyOperator,
+        instance: &ATSP<C>,
+        tour: &mut Vec<u32>,
+        k: usize,
+        ctx: &mut Context<C>,
+    ) -> (Vec<u32>, C) {
+        let n = tour.len();
+        let mut gain = C::ZERO;
+        let removed: Vec<u32> = match operator {
+            DestroyOperator::Random => {
+                let mut cities = tour.clone();
+                utils::shuffle(&mut cities, &mut self.rng);
+                cities.truncate(k);
+                cities
+            }
+            DestroyOperator::Worst => {
+                let mut removed = Vec::with_capacity(k);
+                for _ in 0..k {
+                    // The gains of the neighbors change with every removal
+                    let mut gains: Vec<(C, usize)> = (0..tour.len())
+                        .map(|i| (removal_gain(instance, tour, i), i))
+                        .collect();
+                    ctx.evaluations += tour.len() as u32;
+                    gains.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(Ordering::Equal));
+                    let y: f64 = self.rng.gen();
+                    let (city_gain, i) =
+                        gains[(y.powi(WORST_REMOVAL_POWER) * gains.len() as f64) as usize];
+                    removed.push(tour.remove(i));
+                    gain += city_gain;
+                }
+                return (removed, gain);
+            }
+            DestroyOperator::Related => {
+                let seed_city = tour[self.rng.gen_range(0..n)] as usize;
+                let relatedness = |city: u32| {
+                    let city = city as usize;
+                    let (a, b) = (
+                        instance.cost(seed_city, city),
+                        instance.cost(city, seed_city),
+                    );
+                    if a < b {
+                        a
+                    } else {
+                        b
+                    }
+                };
+                let mut cities: Vec<u32> = tour
+                    .iter()
+                    .copied()
+                    .filter(|&c| c as usize != seed_city)
+                    .collect();
+                cities.sort_by(|&a, &b| {
+                    relatedness(a)
+                        .partial_cmp(&relatedness(b))
+                        .unwrap_or(Ordering::Equal)
+                });
+                cities.truncate(k.saturating_sub(1));
+                cities.insert(0, seed_city as u32);
+                cities.truncate(k);
+                cities
+            }
+        };
+        for &city in removed.iter() {
+            let i = tour.iter().position(|&c| c == city).unwrap();
+            gain += remove_at(instance, tour, i, ctx);
+        }
+        (removed, gain)
+    }
+
+    /// Inserts the removed cities back into `tour` and returns the cost added.
+    fn repair<C: Cost>(
+        &mut self,
+        operator: RepairOperator,
+        instance: &ATSP<C>,
+        tour: &mut Vec<u32>,
+        mut removed: Vec<u32>,
+        ctx: &mut Context<C>,
+    ) -> C {
+        let mut added = C::ZERO;
+        match operator {
+            RepairOperator::Cheapest => {
+                utils::shuffle(&mut removed, &mut self.rng);
+                for city in removed {
+                    let ((position, cost), _) = best_insertions(instance, tour, city as usize, ctx);
+                    tour.insert(position + 1, city);
+                    added += cost;
+                }
+            }
+            RepairOperator::Regret => {
+                while !removed.is_empty() {
+                    let mut selected = (0, 0, C::ZERO, None::<C>);
+                    for (idx, &city) in removed.iter().enumerate() {
+                        let ((position, best), second) =
+                            best_insertions(instance, tour, city as usize, ctx);
+                        let regret = second - best;
+                        if selected.3.is_none_or(|max_regret| regret > max_regret) {
+                            selected = (idx, position, best, Some(regret));
+                        }
+                    }
+                    let city = removed.swap_remove(selected.0);
+                    tour.insert(selected.1 + 1, city);
+                    added += selected.2;
+                }
+            }
+        }
+        added
+    }
+}
+
+impl<C: Cost> Explorer<C> for AlnsExplorer {
+    fn explore(&mut self, instance: &ATSP<C>, solution: &mut Solution, ctx: &mut Context<C>) {
+        let temperature = *self
+            .temperature
+            .get_or_insert(-START_WORSENING * ctx.current_cost.to_f64() / 0.5f64.ln());
+        let destroy_idx = select_operator(&self.destroy_stats, &mut self.rng);
+        let repair_idx = select_operator(&self.repair_stats, &mut self.rng);
+
+        let mut tour = solution.order.clone();
+        let k = self.destroy_size(tour.len());
+        if k == 0 {
+            return;
+        }
+        let (removed, gain) =
+            self.destroy(DESTROY_OPERATORS[destroy_idx], instance, &mut tour, k, ctx);
+        let added = self.repair(
+            REPAIR_OPERATORS[repair_idx],
+            instance,
+            &mut tour,
+            removed,
+            ctx,
+        );
+        let candidate = Solution::new(&tour).unwrap();
+        let candidate_cost = ctx.current_cost - gain + added;
+
+        let cost_change = (candidate_cost - ctx.current_cost).to_f64();
+        let accept = cost_change < 0.0
+            || utils::generate_decision((-cost_change / temperature).exp(), &mut self.rng);
+        let score = if candidate_cost < ctx.best_cost {
+            SCORE_NEW_BEST
+        } else if candidate_cost < ctx.current_cost {
+            SCORE_IMPROVED
+        } else if accept {
+            SCORE_ACCEPTED
+        } else {
+            0.0
+        };
+        if accept {
+            *solution = candidate;
+            ctx.current_cost = candidate_cost;
+            ctx.steps += 1;
+        }
+
+        self.destroy_stats[destroy_idx].update(score, self.decay);
+        self.repair_stats[repair_idx].update(score, self.decay);
+        self.temperature = Some(temperature * self.cooling);
+    }
+
+    fn stop_condition(&self, ctx: &Context<C>) -> bool {
+        ctx.iterations_without_improvement >= self.patience
+    }
+
+    fn summary(&self) -> Option<String> {
+        let mut lines = vec!["Operator (weight, uses, successes):".to_string()];
+        let destroy = DESTROY_OPERATORS
+            .iter()
+            .map(|op| format!("destroy {:?}", op));
+        let repair = REPAIR_OPERATORS.iter().map(|op| format!("repair {:?}", op));
+        let stats = self.destroy_stats.iter().chain(self.repair_stats.iter());
+        for (name, s) in destroy.chain(repair).zip(stats) {
+            lines.push(format!(
+                "  {}: {:.3}, {}, {}",
+                name, s.weight, s.uses, s.successes
+            ));
+        }
+        if let Some(temperature) = self.temperature {
+            lines.push(format!("Final temperature: {:.3}", temperature));
+        }
+        Some(lines.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::initializers::RandomInitializer;
+    use crate::search::{SearchAlgorithm, SearchResult};
+
+    fn instance() -> ATSP<i32> {
+        ATSP::read_from_file("../data/ALL_atsp/ftv33.atsp").unwrap()
+    }
+
+    #[test]
+    fn destroy_and_repair_keep_a_permutation() {
+        let atsp = instance();
+        let mut explorer = AlnsExplorer::new(0, 0.1, 0.3, 0.8, 0.99, 10);
+        let mut ctx = Context::new(0);
+        for destroy in DESTROY_OPERATORS {
+            for repair in REPAIR_OPERATORS {
+                let mut tour: Vec<u32> = (0..atsp.dimension as u32).collect();
+                let cost = atsp.cost_of_solution(&Solution::new(&tour).unwrap());
+                let (removed, gain) = explorer.destroy(destroy, &atsp, &mut tour, 7, &mut ctx);
+                assert_eq!(removed.len(), 7);
+                assert_eq!(tour.len(), atsp.dimension - 7);
+                let added = explorer.repair(repair, &atsp, &mut tour, removed, &mut ctx);
+                let solution = Solution::new(&tour).unwrap();
+                assert!(atsp.is_solution_valid(&solution).is_ok());
+                assert_eq!(atsp.cost_of_solution(&solution), cost - gain + added);
+            }
+        }
+        assert_eq!(explorer.destroy_size(2), 0);
+        assert_eq!(explorer.destroy_size(3), 1);
+    }
+
+    #[test]
+    fn alns_improves_and_reports_true_cost() {
+        let atsp = instance();
+        let mut explorer = AlnsExplorer::new(3, 0.05, 0.2, 0.8, 0.995, 200);
+        let mut initializer = RandomInitializer::new(3);
+        let SearchResult {
+            solution,
+            context: ctx,
+            ..
+        } = SearchAlgorithm::new(&atsp, &mut initializer, &mut explorer, -1).run();
+        assert_eq!(atsp.cost_of_solution(&solution), ctx.best_cost);
+        assert!(ctx.best_cost < ctx.initial_cost);
+    }
+}
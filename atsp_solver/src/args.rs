@@ -1,47 +1,292 @@
-use clap::{Parser, ValueEnum};
+use clap::parser::ValueSource;
+use clap::{ArgMatches, Args, CommandFactory, Parser, Subcommand, ValueEnum};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::ffi::OsString;
 
-#[derive(ValueEnum, Debug, Clone)]
+#[derive(ValueEnum, Serialize, Debug, Clone)]
+#[serde(rename_all = "kebab-case")]
 pub enum Algorithm {
     Random,
     RandomWalk,
-    NNHeuristic,
+    Construction,
     GreedySearch,
-    GreedySearchNN,
     SteepestSearch,
-    SteepestSearchNN,
     SimulatedAnnealing,
-    SimulatedAnnealingNN,
     TabuSearch,
-    TabuSearchNN,
+    ReactiveTabuSearch,
+    ThresholdAccepting,
+    GreatDeluge,
+    RecordToRecord,
+    LateAcceptance,
+    GuidedLocalSearch,
+    Alns,
+    Grasp,
+    ParallelTempering,
+    IslandModel,
+}
+
+#[derive(ValueEnum, Serialize, Debug, Clone)]
+#[serde(rename_all = "kebab-case")]
+pub enum Init {
+    Random,
+    NearestNeighbor,
+    GreedyArc,
+    CheapestInsertion,
+    FarthestInsertion,
+    RandomInsertion,
+}
+
+pub fn init_as_str(init: &Init) -> &str {
+    match init {
+        Init::Random => "random",
+        Init::NearestNeighbor => "nearest-neighbor",
+        Init::GreedyArc => "greedy-arc",
+        Init::CheapestInsertion => "cheapest-insertion",
+        Init::FarthestInsertion => "farthest-insertion",
+        Init::RandomInsertion => "random-insertion",
+    }
+}
+
+#[derive(ValueEnum, Serialize, Debug, Clone)]
+#[serde(rename_all = "kebab-case")]
+pub enum Descent {
+    Steepest,
+    Greedy,
+}
+
+#[derive(ValueEnum, Serialize, Debug, Clone)]
+#[serde(rename_all = "kebab-case")]
+pub enum TabuAttribute {
+    Arcs,
+    Cities,
+}
+
+#[derive(ValueEnum, Serialize, Debug, Clone)]
+#[serde(rename_all = "kebab-case")]
+pub enum TenureMode {
+    Fixed,
+    Random,
+    Reactive,
+}
+
+#[derive(ValueEnum, Serialize, Debug, Clone)]
+#[serde(rename_all = "kebab-case")]
+pub enum OperatorSelection {
+    Uniform,
+    ProbabilityMatching,
+    AdaptivePursuit,
+    Ucb,
+}
+
+#[derive(ValueEnum, Serialize, Debug, Clone)]
+#[serde(rename_all = "kebab-case")]
+pub enum Topology {
+    Ring,
+    Full,
+}
+
+#[derive(ValueEnum, Serialize, Debug, Clone)]
+#[serde(rename_all = "kebab-case")]
+pub enum CostType {
+    I32,
+    I64,
+    F64,
+}
+
+#[derive(ValueEnum, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Format {
+    /// Result with the convergence history
+    Json,
+    /// Result on a single line appended to the file
+    Jsonl,
+    /// Summary row appended to the file, the header is written to a new file
+    Csv,
+    /// Solution in the TSPLIB TOUR format
+    Tour,
+}
+
+impl Format {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Json => "json",
+            Format::Jsonl => "jsonl",
+            Format::Csv => "csv",
+            Format::Tour => "tour",
+        }
+    }
 }
 
 pub fn alg_as_str(alg: &Algorithm) -> &str {
     match alg {
         Algorithm::Random => "random",
         Algorithm::RandomWalk => "random-walk",
-        Algorithm::NNHeuristic => "nn-heuristic",
+        Algorithm::Construction => "construction",
         Algorithm::GreedySearch => "greedy-search",
         Algorithm::SteepestSearch => "steepest-search",
-        Algorithm::GreedySearchNN => "greedy-search-nn",
-        Algorithm::SteepestSearchNN => "steepest-search-nn",
         Algorithm::SimulatedAnnealing => "simulated-annealing",
-        Algorithm::SimulatedAnnealingNN => "simulated-annealing-nn",
         Algorithm::TabuSearch => "tabu-search",
-        Algorithm::TabuSearchNN => "tabu-search-nn",
+        Algorithm::ReactiveTabuSearch => "reactive-tabu-search",
+        Algorithm::ThresholdAccepting => "threshold-accepting",
+        Algorithm::GreatDeluge => "great-deluge",
+        Algorithm::RecordToRecord => "record-to-record",
+        Algorithm::LateAcceptance => "late-acceptance",
+        Algorithm::GuidedLocalSearch => "guided-local-search",
+        Algorithm::Alns => "alns",
+        Algorithm::Grasp => "grasp",
+        Algorithm::ParallelTempering => "parallel-tempering",
+        Algorithm::IslandModel => "island-model",
+    }
+}
+
+/// Parses a finite float accepted by `valid`, otherwise reports `expected`.
+fn parse_f64(value: &str, valid: fn(f64) -> bool, expected: &str) -> Result<f64, String> {
+    let x: f64 = value.parse().map_err(|e| format!("{}", e))?;
+    if x.is_finite() && valid(x) {
+        Ok(x)
+    } else {
+        Err(format!("must be {}", expected))
+    }
+}
+
+fn positive(value: &str) -> Result<f64, String> {
+    parse_f64(value, |x| x > 0.0, "greater than 0")
+}
+
+fn non_negative(value: &str) -> Result<f64, String> {
+    parse_f64(value, |x| x >= 0.0, "at least 0")
+}
+
+fn fraction(value: &str) -> Result<f64, String> {
+    parse_f64(value, |x| x > 0.0 && x <= 1.0, "in (0, 1]")
+}
+
+fn cooling_rate(value: &str) -> Result<f64, String> {
+    parse_f64(value, |x| x > 0.0 && x < 1.0, "in (0, 1)")
+}
+
+fn unit_interval(value: &str) -> Result<f64, String> {
+    parse_f64(value, |x| (0.0..=1.0).contains(&x), "in [0, 1]")
+}
+
+fn at_least_one(value: &str) -> Result<usize, String> {
+    match value.parse() {
+        Ok(0) => Err("must be at least 1".to_string()),
+        parsed => parsed.map_err(|e| format!("{}", e)),
     }
 }
 
-#[derive(Parser, Debug)]
-#[command(name = "ATSP", about = "Solve ATSP problems")]
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Run a grid of experiments described by a config of scripts/configs
+    Bench(BenchOpt),
+    /// Race sampled hyperparameters of an algorithm on training instances
+    Tune(TuneOpt),
+    /// Compare the algorithms of result files with rank-based tests
+    Compare(CompareOpt),
+}
+
+#[derive(Args, Debug)]
+pub struct BenchOpt {
+    /// Path to the experiment config
+    pub config: String,
+
+    /// Name of the JSON Lines results file in the output folder of the config
+    /// Runs already in the file are skipped
+    #[arg(short, long, default_value = "output")]
+    pub output: String,
+
+    /// Number of runs executed in parallel, defaults to the number of cores
+    #[arg(short, long, value_parser = clap::value_parser!(u64).range(1..))]
+    pub jobs: Option<u64>,
+}
+
+#[derive(Args, Debug)]
+pub struct TuneOpt {
+    /// Path to the tuning config with the parameter ranges and training instances
+    pub config: String,
+
+    /// JSON file receiving the elite configurations
+    #[arg(short, long)]
+    pub output: Option<String>,
+
+    /// Number of runs executed in parallel, defaults to the number of cores
+    #[arg(short, long, value_parser = clap::value_parser!(u64).range(1..))]
+    pub jobs: Option<u64>,
+}
+
+#[derive(Args, Debug)]
+pub struct CompareOpt {
+    /// Result files (JSON, JSON array or JSON Lines) or folders of them
+    #[arg(required = true)]
+    pub results: Vec<String>,
+
+    /// Result fields or hyperparameters distinguishing the algorithms besides the method
+    #[arg(long)]
+    pub by: Vec<String>,
+
+    /// Prefix of the CSV and Markdown reports
+    #[arg(short, long)]
+    pub output: Option<String>,
+
+    /// Significance level of the tests
+    #[arg(long, default_value_t = 0.05, value_parser = fraction)]
+    pub alpha: f64,
+}
+
+/// Options are resolved from the command line on top of an optional `--config` file.
+/// Serializes to a config file reproducing the run.
+#[derive(Parser, Serialize, Debug)]
+#[command(
+    name = "ATSP",
+    about = "Solve ATSP problems",
+    args_override_self = true,
+    subcommand_negates_reqs = true,
+    args_conflicts_with_subcommands = true
+)]
+#[serde(rename_all = "kebab-case")]
 pub struct Opt {
+    #[command(subcommand)]
+    #[serde(skip)]
+    pub command: Option<Command>,
+
+    /// JSON or TOML file with option values keyed by their long names
+    /// Options given on the command line override the file
+    #[arg(long)]
+    #[serde(skip)]
+    pub config: Option<String>,
+
     /// Path to the file with the ATSP problem
-    #[arg(short, long)]
+    // Defaults only fill in the options of subcommands, which do not need them
+    #[arg(
+        short,
+        long,
+        required = true,
+        default_value = "",
+        hide_default_value = true
+    )]
     pub instance: String,
 
     /// Seed for the random number generator
     #[arg(short, long, default_value = "0")]
     pub seed: u64,
 
+    /// Number of independent restarts of the algorithm
+    /// Seeds of the restarts are derived from the seed parameter
+    #[arg(long, default_value = "1", value_parser = clap::value_parser!(u64).range(1..))]
+    pub restarts: u64,
+
+    /// Number of worker threads running the restarts
+    /// A single job runs the restarts sequentially
+    #[arg(short, long, default_value = "1", value_parser = clap::value_parser!(u64).range(1..))]
+    pub jobs: u64,
+
+    /// Number of threads evaluating the neighborhood
+    /// Only used for steepest search and tabu search
+    #[arg(long, default_value = "1", value_parser = clap::value_parser!(u64).range(1..))]
+    pub threads: u64,
+
     /// Print output in a verbose mode
     #[arg(short, long)]
     pub verbose: bool,
@@ -50,18 +295,70 @@ pub struct Opt {
     #[arg(short, long, default_value = "-1")]
     pub max_time_ns: i64,
 
+    /// Maximum number of evaluations
+    /// Allows comparing algorithms on the same evaluation budget
+    #[arg(long)]
+    pub max_evaluations: Option<u32>,
+
+    /// Record the current and best costs every given number of evaluations
+    #[arg(long)]
+    pub sample_interval: Option<u32>,
+
+    /// Stop once the best cost is at most the target, such as the best known cost
+    #[arg(long)]
+    pub target_cost: Option<f64>,
+
+    /// Stop after the given number of evaluations without improving the best cost
+    #[arg(long)]
+    pub stagnation: Option<u32>,
+
+    /// Print the best cost to stderr while searching
+    #[arg(long)]
+    pub progress: bool,
+
+    /// Path of a CSV receiving the best cost at every improvement while the search runs
+    #[arg(long, default_value = "")]
+    pub trace: String,
+
     /// Algorithm to use
-    #[arg(short, long, value_enum)]
+    #[arg(
+        short,
+        long,
+        value_enum,
+        required = true,
+        default_value = "random",
+        hide_default_value = true
+    )]
     pub algorithm: Algorithm,
 
+    /// Construction of the starting solution, also the result of the construction algorithm
+    #[arg(long, value_enum, default_value = "random")]
+    pub init: Init,
+
+    /// Start cities of the nearest neighbor initializer, the best tour is kept; 0 uses every city
+    #[arg(long, default_value = "1")]
+    pub nn_starts: usize,
+
+    /// Number of times the initializer is run, the best tour is kept
+    #[arg(long, default_value = "1", value_parser = clap::value_parser!(u32).range(1..))]
+    pub init_repeats: u32,
+
     /// Measure the time of execution
     #[arg(short, long)]
     pub time: bool,
 
-    /// Output file path
+    /// Output file path, its extension is replaced by the extension of every format
     #[arg(short, long, default_value = "")]
     pub output: String,
 
+    /// Formats written to the output path, several can be given separated by commas
+    #[arg(long, value_enum, value_delimiter = ',', default_value = "json")]
+    pub format: Vec<Format>,
+
+    /// Path of a long-format CSV with the best cost after every improvement and the samples
+    #[arg(long, default_value = "")]
+    pub convergence: String,
+
     /// Use edge swap neighborhood
     /// Only used for iterative algorithms
     #[arg(long, default_value = "1")]
@@ -72,21 +369,611 @@ pub struct Opt {
     #[arg(long, default_value = "1")]
     pub node_swap: u32,
 
-    /// Meta parameter 1 for algorithms
-    /// For Simulated Annealing, it is the cooling rate
-    /// For Tabu Search, it is the patience parameter
-    #[arg(long, default_value = "0.5")]
-    pub meta_param_1: f64,
-
-    /// Meta parameter 2 for algorithms
-    /// For Simulated Annealing, it is the initial temperature
-    /// For Tabu Search, it is the elite percentage
-    #[arg(long, default_value = "100.0")]
-    pub meta_param_2: f64,
-
-    /// Meta parameter 3 for algorithms
-    /// For Simulated Annealing, it is the markov chain length multiplier
-    /// For Tabu Search, it is the tabu tenure multiplier
-    #[arg(long, default_value = "1.0")]
-    pub meta_param_3: f64,
+    /// Numeric type of the costs
+    /// Use i64 when tour costs may exceed the i32 range and f64 for fractional distances
+    #[arg(long, value_enum, default_value = "i32")]
+    pub cost_type: CostType,
+
+    /// Store the cost matrix as u16 if all values fit
+    /// Falls back to i32 storage otherwise
+    #[arg(long)]
+    pub narrow_matrix: bool,
+
+    /// Initial temperature of simulated annealing
+    #[arg(long, default_value = "100.0", value_parser = positive, help_heading = "Simulated annealing")]
+    pub sa_t0: f64,
+
+    /// Cooling rate, the temperature is multiplied by it after every Markov chain
+    #[arg(long, default_value = "0.95", value_parser = cooling_rate, help_heading = "Simulated annealing")]
+    pub sa_alpha: f64,
+
+    /// Length of a Markov chain at a fixed temperature, as a multiple of the instance size
+    #[arg(long, default_value = "1.0", value_parser = positive, help_heading = "Simulated annealing")]
+    pub sa_chain_length: f64,
+
+    /// Number of islands of the island model
+    #[arg(long, default_value = "4", value_parser = clap::value_parser!(u64).range(1..), help_heading = "Island model")]
+    pub islands: u64,
+
+    /// Algorithm run on every island
+    /// Initializer of the islands also follows this algorithm
+    #[arg(
+        long,
+        value_enum,
+        default_value = "simulated-annealing",
+        help_heading = "Island model"
+    )]
+    pub island_algorithm: Algorithm,
+
+    /// Migration topology between the islands
+    #[arg(
+        long,
+        value_enum,
+        default_value = "ring",
+        help_heading = "Island model"
+    )]
+    pub topology: Topology,
+
+    /// Number of iterations every island runs between migrations
+    #[arg(long, default_value = "1000", value_parser = clap::value_parser!(u32).range(1..), help_heading = "Island model")]
+    pub migration_interval: u32,
+
+    /// Ratio between the initial temperatures of consecutive simulated annealing islands
+    #[arg(long, default_value = "0.5", value_parser = positive, help_heading = "Island model")]
+    pub island_temperature_spread: f64,
+
+    /// Number of replicas, one per temperature of the ladder
+    #[arg(long, default_value = "8", value_parser = clap::value_parser!(u32).range(1..), help_heading = "Parallel tempering")]
+    pub pt_replicas: u32,
+
+    /// Temperature of the coldest replica
+    #[arg(long, default_value = "1.0", value_parser = positive, help_heading = "Parallel tempering")]
+    pub pt_min_temperature: f64,
+
+    /// Temperature of the hottest replica
+    /// Intermediate temperatures are spaced geometrically
+    #[arg(long, default_value = "100.0", value_parser = positive, help_heading = "Parallel tempering")]
+    pub pt_max_temperature: f64,
+
+    /// Metropolis moves per replica between swap proposals, as a multiple of the instance size
+    #[arg(long, default_value = "1.0", value_parser = positive, help_heading = "Parallel tempering")]
+    pub pt_sweep: f64,
+
+    /// Number of swap rounds without improvement of the best solution before stopping
+    #[arg(long, default_value = "200", help_heading = "Parallel tempering")]
+    pub pt_patience: u32,
+
+    /// Initial threshold of threshold accepting
+    /// Moves worsening the cost by at most the threshold are accepted
+    #[arg(long, default_value = "10.0", value_parser = non_negative, help_heading = "Acceptance criteria")]
+    pub ta_threshold: f64,
+
+    /// Factor applied to the threshold after every move
+    #[arg(long, default_value = "0.9999", value_parser = cooling_rate, help_heading = "Acceptance criteria")]
+    pub ta_decay: f64,
+
+    /// Fraction of the gap between the water level and the best cost removed after every move
+    #[arg(long, default_value = "0.0001", value_parser = fraction, help_heading = "Acceptance criteria")]
+    pub gd_rain_speed: f64,
+
+    /// Allowed relative deviation from the best cost in record-to-record travel
+    #[arg(long, default_value = "0.01", value_parser = non_negative, help_heading = "Acceptance criteria")]
+    pub rrt_deviation: f64,
+
+    /// History length of late acceptance hill climbing
+    #[arg(long, default_value = "1000", value_parser = at_least_one, help_heading = "Acceptance criteria")]
+    pub lahc_history: usize,
+
+    /// Number of moves without improvement of the best solution before stopping
+    #[arg(long, default_value = "100000", help_heading = "Acceptance criteria")]
+    pub acceptance_patience: u32,
+
+    /// Local search run on the penalized costs
+    #[arg(
+        long,
+        value_enum,
+        default_value = "steepest",
+        help_heading = "Guided local search"
+    )]
+    pub gls_descent: Descent,
+
+    /// Penalty weight relative to the average arc cost of the first local optimum
+    #[arg(long, default_value = "0.3", value_parser = unit_interval, help_heading = "Guided local search")]
+    pub gls_alpha: f64,
+
+    /// Number of moves or penalizations without improvement of the best solution before stopping
+    #[arg(long, default_value = "2000", help_heading = "Guided local search")]
+    pub gls_patience: u32,
+
+    /// Smallest fraction of the cities removed by a destroy operator
+    #[arg(
+        long,
+        default_value = "0.02",
+        value_parser = fraction,
+        help_heading = "Adaptive large neighborhood search"
+    )]
+    pub alns_destroy_min: f64,
+
+    /// Largest fraction of the cities removed by a destroy operator
+    #[arg(
+        long,
+        default_value = "0.15",
+        value_parser = fraction,
+        help_heading = "Adaptive large neighborhood search"
+    )]
+    pub alns_destroy_max: f64,
+
+    /// Weight decay of the operators, the share of the old weight kept after every use
+    #[arg(
+        long,
+        default_value = "0.8",
+        value_parser = cooling_rate,
+        help_heading = "Adaptive large neighborhood search"
+    )]
+    pub alns_decay: f64,
+
+    /// Cooling rate of the annealing acceptance, applied after every iteration
+    #[arg(
+        long,
+        default_value = "0.999",
+        value_parser = cooling_rate,
+        help_heading = "Adaptive large neighborhood search"
+    )]
+    pub alns_cooling: f64,
+
+    /// Number of iterations without improvement of the best solution before stopping
+    #[arg(
+        long,
+        default_value = "2000",
+        help_heading = "Adaptive large neighborhood search"
+    )]
+    pub alns_patience: u32,
+
+    /// Strategy choosing the move type of sampled moves (random walk, annealing, acceptance criteria, parallel tempering)
+    #[arg(
+        long,
+        value_enum,
+        default_value = "uniform",
+        help_heading = "Operator selection"
+    )]
+    pub operator_selection: OperatorSelection,
+
+    /// Smallest selection probability of a move type in probability matching and adaptive pursuit
+    #[arg(long, default_value = "0.1", value_parser = unit_interval, help_heading = "Operator selection")]
+    pub os_min_probability: f64,
+
+    /// Adaptation rate of the estimated quality of a move type
+    #[arg(long, default_value = "0.1", value_parser = fraction, help_heading = "Operator selection")]
+    pub os_adaptation_rate: f64,
+
+    /// Learning rate of the probabilities in adaptive pursuit
+    #[arg(long, default_value = "0.1", value_parser = fraction, help_heading = "Operator selection")]
+    pub os_learning_rate: f64,
+
+    /// Exploration weight of the UCB bandit
+    #[arg(long, default_value = "0.5", value_parser = non_negative, help_heading = "Operator selection")]
+    pub os_exploration: f64,
+
+    /// Number of iterations without improvement of the best solution before stopping
+    #[arg(long, default_value = "10", help_heading = "Tabu search")]
+    pub tabu_patience: u32,
+
+    /// Fraction of the neighborhood sampled every iteration, the same fraction of the sample is kept as candidates
+    #[arg(long, default_value = "0.1", value_parser = fraction, help_heading = "Tabu search")]
+    pub tabu_elite: f64,
+
+    /// Tabu tenure as a multiple of the instance size
+    #[arg(long, default_value = "1.0", value_parser = non_negative, help_heading = "Tabu search")]
+    pub tabu_tenure: f64,
+
+    /// Attribute made tabu by a move: its removed arcs or its moved cities
+    #[arg(long, value_enum, default_value = "arcs", help_heading = "Tabu search")]
+    pub tabu_attribute: TabuAttribute,
+
+    /// How the tenure is applied
+    #[arg(
+        long,
+        value_enum,
+        default_value = "fixed",
+        help_heading = "Tabu search"
+    )]
+    pub tabu_tenure_mode: TenureMode,
+
+    /// Relative half-width of the tenure range used by random and reactive tenures
+    #[arg(long, default_value = "0.5", value_parser = unit_interval, help_heading = "Tabu search")]
+    pub tabu_tenure_spread: f64,
+
+    /// Size of the elite pool the reactive tabu search restarts from
+    #[arg(long, default_value = "5", value_parser = at_least_one, help_heading = "Reactive tabu search")]
+    pub rts_elite: usize,
+
+    /// Number of escapes without improvement of the best solution before stopping
+    #[arg(long, default_value = "10", help_heading = "Reactive tabu search")]
+    pub rts_max_escapes: u32,
+
+    /// Number of diversifying moves of an escape, times the number of cities
+    #[arg(long, default_value = "0.1", value_parser = positive, help_heading = "Reactive tabu search")]
+    pub rts_diversification: f64,
+
+    /// Greediness of the randomized nearest neighbor construction, 0 is greedy and 1 is random
+    #[arg(long, default_value = "0.2", value_parser = unit_interval, help_heading = "GRASP")]
+    pub grasp_alpha: f64,
+
+    /// Local search improving every constructed tour
+    #[arg(long, value_enum, default_value = "steepest", help_heading = "GRASP")]
+    pub grasp_descent: Descent,
+
+    /// Number of constructions without improvement of the best solution before stopping
+    #[arg(long, default_value = "50", help_heading = "GRASP")]
+    pub grasp_patience: u32,
+
+    /// Relink every local optimum towards a solution of the elite pool
+    #[arg(long, help_heading = "GRASP")]
+    pub grasp_path_relinking: bool,
+
+    /// Size of the elite pool used by path relinking
+    #[arg(long, default_value = "5", value_parser = at_least_one, help_heading = "GRASP")]
+    pub grasp_elite: usize,
+}
+
+impl Opt {
+    /// Numeric parameters of the algorithm, named after their options.
+    pub fn hyperparameters(&self, algorithm: &Algorithm) -> Vec<(&'static str, f64)> {
+        let mut params = match algorithm {
+            Algorithm::Random
+            | Algorithm::RandomWalk
+            | Algorithm::Construction
+            | Algorithm::GreedySearch
+            | Algorithm::SteepestSearch => vec![],
+            Algorithm::SimulatedAnnealing => vec![
+                ("sa-t0", self.sa_t0),
+                ("sa-alpha", self.sa_alpha),
+                ("sa-chain-length", self.sa_chain_length),
+            ],
+            Algorithm::TabuSearch => vec![
+                ("tabu-patience", self.tabu_patience as f64),
+                ("tabu-elite", self.tabu_elite),
+                ("tabu-tenure", self.tabu_tenure),
+                ("tabu-tenure-spread", self.tabu_tenure_spread),
+            ],
+            Algorithm::ReactiveTabuSearch => vec![
+                ("tabu-patience", self.tabu_patience as f64),
+                ("tabu-elite", self.tabu_elite),
+                ("tabu-tenure", self.tabu_tenure),
+                ("tabu-tenure-spread", self.tabu_tenure_spread),
+                ("rts-elite", self.rts_elite as f64),
+                ("rts-max-escapes", self.rts_max_escapes as f64),
+                ("rts-diversification", self.rts_diversification),
+            ],
+            Algorithm::ThresholdAccepting => vec![
+                ("ta-threshold", self.ta_threshold),
+                ("ta-decay", self.ta_decay),
+                ("acceptance-patience", self.acceptance_patience as f64),
+            ],
+            Algorithm::GreatDeluge => vec![
+                ("gd-rain-speed", self.gd_rain_speed),
+                ("acceptance-patience", self.acceptance_patience as f64),
+            ],
+            Algorithm::RecordToRecord => vec![
+                ("rrt-deviation", self.rrt_deviation),
+                ("acceptance-patience", self.acceptance_patience as f64),
+            ],
+            Algorithm::LateAcceptance => vec![
+                ("lahc-history", self.lahc_history as f64),
+                ("acceptance-patience", self.acceptance_patience as f64),
+            ],
+            Algorithm::GuidedLocalSearch => vec![
+                ("gls-alpha", self.gls_alpha),
+                ("gls-patience", self.gls_patience as f64),
+            ],
+            Algorithm::Alns => vec![
+                ("alns-destroy-min", self.alns_destroy_min),
+                ("alns-destroy-max", self.alns_destroy_max),
+                ("alns-decay", self.alns_decay),
+                ("alns-cooling", self.alns_cooling),
+                ("alns-patience", self.alns_patience as f64),
+            ],
+            Algorithm::Grasp => vec![
+                ("grasp-alpha", self.grasp_alpha),
+                ("grasp-patience", self.grasp_patience as f64),
+                ("grasp-elite", self.grasp_elite as f64),
+            ],
+            Algorithm::ParallelTempering => vec![
+                ("pt-replicas", self.pt_replicas as f64),
+                ("pt-min-temperature", self.pt_min_temperature),
+                ("pt-max-temperature", self.pt_max_temperature),
+                ("pt-sweep", self.pt_sweep),
+                ("pt-patience", self.pt_patience as f64),
+            ],
+            Algorithm::IslandModel => {
+                let mut params = vec![
+                    ("islands", self.islands as f64),
+                    ("migration-interval", self.migration_interval as f64),
+                    ("island-temperature-spread", self.island_temperature_spread),
+                ];
+                params.extend(self.hyperparameters(&self.island_algorithm));
+                params
+            }
+        };
+        let samples_moves = matches!(
+            algorithm,
+            Algorithm::RandomWalk
+                | Algorithm::SimulatedAnnealing
+                | Algorithm::ThresholdAccepting
+                | Algorithm::GreatDeluge
+                | Algorithm::RecordToRecord
+                | Algorithm::LateAcceptance
+                | Algorithm::ParallelTempering
+        );
+        if samples_moves && !matches!(self.operator_selection, OperatorSelection::Uniform) {
+            params.extend([
+                ("os-min-probability", self.os_min_probability),
+                ("os-adaptation-rate", self.os_adaptation_rate),
+                ("os-learning-rate", self.os_learning_rate),
+                ("os-exploration", self.os_exploration),
+            ]);
+        }
+        params
+    }
+
+    /// Help headings of the options read by the algorithm.
+    fn used_headings(&self, algorithm: &Algorithm) -> Vec<&'static str> {
+        match algorithm {
+            Algorithm::Random
+            | Algorithm::Construction
+            | Algorithm::GreedySearch
+            | Algorithm::SteepestSearch => vec![],
+            Algorithm::RandomWalk => vec!["Operator selection"],
+            Algorithm::SimulatedAnnealing => vec!["Simulated annealing", "Operator selection"],
+            Algorithm::TabuSearch => vec!["Tabu search"],
+            Algorithm::ReactiveTabuSearch => vec!["Tabu search", "Reactive tabu search"],
+            Algorithm::ThresholdAccepting
+            | Algorithm::GreatDeluge
+            | Algorithm::RecordToRecord
+            | Algorithm::LateAcceptance => vec!["Acceptance criteria", "Operator selection"],
+            Algorithm::GuidedLocalSearch => vec!["Guided local search"],
+            Algorithm::Alns => vec!["Adaptive large neighborhood search"],
+            Algorithm::Grasp => vec!["GRASP"],
+            Algorithm::ParallelTempering => vec!["Parallel tempering", "Operator selection"],
+            Algorithm::IslandModel => {
+                let mut headings = vec!["Island model"];
+                headings.extend(self.used_headings(&self.island_algorithm));
+                headings
+            }
+        }
+    }
+
+    /// Checks the options depending on each other and rejects options of other algorithms
+    /// given on the command line, whose matches are `cli`.
+    fn validate(&self, cli: &ArgMatches) -> Result<(), String> {
+        if self.pt_min_temperature > self.pt_max_temperature {
+            return Err("--pt-min-temperature must be at most --pt-max-temperature".to_string());
+        }
+        if self.alns_destroy_min > self.alns_destroy_max {
+            return Err("--alns-destroy-min must be at most --alns-destroy-max".to_string());
+        }
+        let headings = self.used_headings(&self.algorithm);
+        for arg in Opt::command().get_arguments() {
+            let (Some(name), Some(heading)) = (arg.get_long(), arg.get_help_heading()) else {
+                continue;
+            };
+            let given = cli.value_source(arg.get_id().as_str()) == Some(ValueSource::CommandLine);
+            if given && !headings.contains(&heading) {
+                return Err(format!(
+                    "--{} is not used by {}",
+                    name,
+                    alg_as_str(&self.algorithm)
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Opt {
+    /// Parses the command line on top of the arguments read from its `--config` file.
+    /// Options of other algorithms are only rejected on the command line, since the config
+    /// of a finished run lists every option.
+    pub fn resolve(cli: Vec<OsString>) -> Result<Opt, clap::Error> {
+        let mut args: Vec<OsString> = cli.iter().take(1).cloned().collect();
+        if let Some(path) = config_path(&cli) {
+            let file_args = config_file_args(&path)
+                .map_err(|e| clap::Error::raw(clap::error::ErrorKind::Io, format!("{}\n", e)))?;
+            args.extend(file_args);
+        }
+        args.extend(cli.iter().skip(1).cloned());
+        let opt = Opt::try_parse_from(args)?;
+        if opt.command.is_none() {
+            // The command line alone may miss options required from the config file
+            let cli = Opt::command().ignore_errors(true).get_matches_from(cli);
+            opt.validate(&cli).map_err(|e| {
+                clap::Error::raw(clap::error::ErrorKind::ArgumentConflict, format!("{}\n", e))
+            })?;
+        }
+        Ok(opt)
+    }
+}
+
+fn config_path(cli: &[OsString]) -> Option<String> {
+    let mut args = cli.iter().skip(1).map(|arg| arg.to_string_lossy());
+    while let Some(arg) = args.next() {
+        if arg == "--config" {
+            return args.next().map(|path| path.into_owned());
+        }
+        if let Some(path) = arg.strip_prefix("--config=") {
+            return Some(path.to_string());
+        }
+    }
+    None
+}
+
+/// Reads a config file; files ending in `.toml` are TOML, others JSON.
+pub fn read_config_file<T: DeserializeOwned>(path: &str) -> Result<T, Box<dyn std::error::Error>> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("Cannot read config file {}: {}", path, e))?;
+    if path.ends_with(".toml") {
+        Ok(toml::from_str(&text)?)
+    } else {
+        Ok(serde_json::from_str(&text)?)
+    }
+}
+
+/// Reads a config file as command line arguments.
+pub fn config_file_args(path: &str) -> Result<Vec<OsString>, Box<dyn std::error::Error>> {
+    Ok(config_args(read_config_file(path)?)?)
+}
+
+/// Turns option values into `--name=value` arguments. Keys may be written with
+/// underscores or leading dashes, `true` enables a flag and `false` or `null` leaves it out.
+pub fn config_args(
+    values: serde_json::Map<String, serde_json::Value>,
+) -> Result<Vec<OsString>, String> {
+    let mut args = Vec::new();
+    for (key, value) in values {
+        let name = key.trim_start_matches('-').replace('_', "-");
+        if name == "config" {
+            return Err("A config file cannot include another config file".to_string());
+        }
+        match value {
+            serde_json::Value::Bool(true) => args.push(format!("--{}", name).into()),
+            serde_json::Value::Bool(false) | serde_json::Value::Null => {}
+            serde_json::Value::Number(number) => args.push(format!("--{}={}", name, number).into()),
+            serde_json::Value::String(text) => args.push(format!("--{}={}", name, text).into()),
+            serde_json::Value::Array(values) => {
+                let values: Vec<String> = values
+                    .iter()
+                    .map(|value| match value {
+                        serde_json::Value::String(text) => text.clone(),
+                        value => value.to_string(),
+                    })
+                    .collect();
+                args.push(format!("--{}={}", name, values.join(",")).into())
+            }
+            _ => {
+                return Err(format!(
+                    "Value of {} must be a number, string, boolean or array",
+                    key
+                ))
+            }
+        }
+    }
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolve(cli: &[&str]) -> Result<Opt, clap::Error> {
+        Opt::resolve(cli.iter().map(OsString::from).collect())
+    }
+
+    #[test]
+    fn command_line_overrides_config_file() {
+        let path = std::env::temp_dir().join("evo_bio_config.toml");
+        std::fs::write(
+            &path,
+            "instance = \"ftv33.atsp\"\nalgorithm = \"simulated-annealing\"\nsa_t0 = 50\nmax-time-ns = -1\nverbose = true\n",
+        )
+        .unwrap();
+        let config = format!("--config={}", path.display());
+        let args = resolve(&["atsp", &config, "--sa-t0", "20"]).unwrap();
+        assert_eq!(args.instance, "ftv33.atsp");
+        assert!(matches!(args.algorithm, Algorithm::SimulatedAnnealing));
+        assert_eq!(args.sa_t0, 20.0);
+        assert_eq!(args.max_time_ns, -1);
+        assert!(args.verbose);
+    }
+
+    #[test]
+    fn resolved_options_reproduce_the_run() {
+        let args = resolve(&[
+            "atsp",
+            "-i",
+            "p43.atsp",
+            "-a",
+            "tabu-search",
+            "--tabu-elite",
+            "0.2",
+            "--format",
+            "jsonl,tour",
+        ])
+        .unwrap();
+        let serde_json::Value::Object(values) = serde_json::to_value(&args).unwrap() else {
+            panic!("options must serialize to an object");
+        };
+        // The config lists the options of every algorithm, which only a config file may hold
+        let mut cli = vec![OsString::from("atsp")];
+        cli.extend(config_args(values.clone()).unwrap());
+        assert!(Opt::resolve(cli).is_err());
+        let path = std::env::temp_dir().join("evo_bio_replay.json");
+        std::fs::write(&path, serde_json::Value::Object(values).to_string()).unwrap();
+        let mut replayed = resolve(&["atsp", &format!("--config={}", path.display())]).unwrap();
+        replayed.config = None;
+        assert_eq!(format!("{:?}", replayed), format!("{:?}", args));
+    }
+
+    #[test]
+    fn config_values_are_validated() {
+        let mut values = serde_json::Map::new();
+        values.insert("sa-alpha".to_string(), serde_json::json!(2.0));
+        let mut cli = vec![
+            OsString::from("atsp"),
+            "-i".into(),
+            "x".into(),
+            "-a".into(),
+            "random".into(),
+        ];
+        cli.extend(config_args(values).unwrap());
+        assert!(Opt::resolve(cli).is_err());
+    }
+
+    #[test]
+    fn dependent_and_foreign_options_are_rejected() {
+        let base = ["atsp", "-i", "x", "-a"];
+        let parse = |extra: &[&str]| resolve(&[&base[..], extra].concat());
+        assert!(parse(&["parallel-tempering", "--pt-min-temperature", "0"]).is_err());
+        assert!(parse(&[
+            "parallel-tempering",
+            "--pt-min-temperature",
+            "10",
+            "--pt-max-temperature",
+            "5"
+        ])
+        .is_err());
+        assert!(parse(&[
+            "alns",
+            "--alns-destroy-min",
+            "0.5",
+            "--alns-destroy-max",
+            "0.2"
+        ])
+        .is_err());
+        assert!(parse(&["alns", "--alns-cooling", "1"]).is_err());
+        assert!(parse(&["grasp", "--grasp-alpha", "1.5"]).is_err());
+        assert!(parse(&["tabu-search", "--sa-t0", "50"]).is_err());
+        assert!(parse(&["tabu-search", "--operator-selection", "ucb"]).is_err());
+        assert!(parse(&["tabu-search", "--sa-t0", "100"]).is_err());
+        assert!(parse(&["tabu-search", "--grasp-path-relinking"]).is_err());
+        assert!(parse(&["parallel-tempering", "--pt-replicas", "0"]).is_err());
+        assert!(parse(&["late-acceptance", "--lahc-history", "0"]).is_err());
+        assert!(parse(&["threshold-accepting", "--ta-threshold", "-1"]).is_err());
+        assert!(parse(&[
+            "island-model",
+            "--island-algorithm",
+            "tabu-search",
+            "--tabu-elite",
+            "0.2"
+        ])
+        .is_ok());
+        assert!(parse(&["simulated-annealing", "--os-exploration", "1"]).is_ok());
+    }
+
+    #[test]
+    fn island_options_are_validated() {
+        let parse = |option: &str| resolve(&["atsp", "-i", "x", "-a", "island-model", option]);
+        assert!(parse("--migration-interval=0").is_err());
+        assert!(parse("--island-temperature-spread=-2").is_err());
+        assert!(parse("--migration-interval=1").is_ok());
+    }
 }
@@ -1,19 +1,24 @@
+use crate::cost::Cost;
 use crate::errors::MyError;
+use crate::matrix::{CostMatrix, Matrix};
+use crate::operation::MAX_NODES;
 use crate::solution::Solution;
 use std::collections::HashSet;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 
-pub struct ATSP {
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone)]
+pub struct ATSP<C: Cost> {
     pub name: String,
     pub comment: String,
     pub dimension: usize,
     pub edge_weight_type: String,
     pub edge_weight_format: String,
-    pub matrix: Vec<Vec<i32>>,
+    pub matrix: Matrix<C>,
 }
 
-impl ATSP {
+impl<C: Cost> ATSP<C> {
     pub fn read_from_file(file_path: &str) -> Result<Self, Box<dyn std::error::Error>> {
         let file = File::open(file_path)?;
         let reader = BufReader::new(file);
@@ -23,7 +28,6 @@ impl ATSP {
         let mut dimension = 0;
         let mut edge_weight_type = String::new();
         let mut edge_weight_format = String::new();
-        let mut matrix = Vec::new();
         let mut read_matrix = false;
         let mut all_values = Vec::new();
 
@@ -44,18 +48,22 @@ impl ATSP {
             } else if line == "EOF" {
                 break;
             } else if read_matrix {
-                let values: Vec<i32> = line
+                let values: Vec<C> = line
                     .split_whitespace()
-                    .map(|n| n.parse().unwrap_or(std::i32::MAX))
+                    .map(|n| n.parse().unwrap_or(C::unreachable(dimension)))
                     .collect();
                 all_values.extend(values);
             }
         }
 
-        for i in 0..dimension {
-            let row = all_values[i * dimension..(i + 1) * dimension].to_vec();
-            matrix.push(row);
+        if dimension < 3 {
+            return Err(MyError::TooFewCities.into());
         }
+        if dimension > MAX_NODES as usize {
+            return Err(MyError::TooManyCities.into());
+        }
+        all_values.truncate(dimension * dimension);
+        let matrix = Matrix::Wide(CostMatrix::new(dimension, all_values)?);
 
         Ok(Self {
             name,
@@ -67,27 +75,46 @@ impl ATSP {
         })
     }
 
-    pub fn display(&self, with_matrix: bool) {
-        println!("Name: {}", self.name);
-        println!("Comment: {}", self.comment);
-        println!("Dimension: {}", self.dimension);
-        println!("Edge Weight Type: {}", self.edge_weight_type);
-        println!("Edge Weight Format: {}", self.edge_weight_format);
+    /// Human readable header of the instance, optionally followed by its matrix.
+    pub fn describe(&self, with_matrix: bool) -> String {
+        let mut description = format!(
+            "Name: {}\nComment: {}\nDimension: {}\nEdge Weight Type: {}\nEdge Weight Format: {}",
+            self.name, self.comment, self.dimension, self.edge_weight_type, self.edge_weight_format
+        );
 
         // Added ommit as large matrix is too big to meaningfully display
         if !with_matrix {
-            return;
+            return description;
         }
-        println!("\nMatrix:");
-        for row in &self.matrix {
-            print!(
-                "{}\n",
-                row.iter()
-                    .map(|&val| format!("{:4}", val))
+        description.push_str("\n\nMatrix:");
+        for i in 0..self.dimension {
+            description.push('\n');
+            description.push_str(
+                &self
+                    .matrix
+                    .row(i)
+                    .map(|val| format!("{:4}", val))
                     .collect::<Vec<_>>()
-                    .join(" ")
+                    .join(" "),
             );
         }
+        description
+    }
+
+    /// Switches the cost matrix to `u16` storage if all values fit.
+    /// Returns whether the narrow storage is in use.
+    pub fn narrow_matrix(&mut self) -> bool {
+        if let Matrix::Wide(matrix) = &self.matrix {
+            if let Some(narrow) = matrix.narrowed() {
+                self.matrix = Matrix::Narrow(narrow);
+            }
+        }
+        self.matrix.is_narrow()
+    }
+
+    #[inline(always)]
+    pub fn cost(&self, from: usize, to: usize) -> C {
+        self.matrix.get(from, to)
     }
 
     pub fn is_solution_valid(&self, solution: &Solution) -> Result<(), MyError> {
@@ -107,11 +134,13 @@ impl ATSP {
         Ok(())
     }
 
-    pub fn cost_of_solution(&self, solution: &Solution) -> i32 {
-        let mut cost = 0;
+    pub fn cost_of_solution(&self, solution: &Solution) -> C {
+        let mut cost = C::ZERO;
         for i in 0..self.dimension {
-            cost += self.matrix[solution.order[i] as usize]
-                [solution.order[(i + 1) % self.dimension] as usize];
+            cost += self.cost(
+                solution.order[i] as usize,
+                solution.order[(i + 1) % self.dimension] as usize,
+            );
         }
         cost
     }
@@ -125,19 +154,46 @@ mod tests {
 
     #[test]
     fn read_from_file() {
-        let atsp = ATSP::read_from_file(&format!("{}/ftv170.atsp", DATA_PATH)).unwrap();
+        let atsp: ATSP<i32> = ATSP::read_from_file(&format!("{}/ftv170.atsp", DATA_PATH)).unwrap();
         assert_eq!(atsp.name, "ftv170");
         assert_eq!(atsp.comment, "Asymmetric TSP (Fischetti)");
         assert_eq!(atsp.dimension, 171);
         assert_eq!(atsp.edge_weight_type, "EXPLICIT");
         assert_eq!(atsp.edge_weight_format, "FULL_MATRIX");
-        assert_eq!(atsp.matrix.len(), 171);
-        assert_eq!(atsp.matrix[0].len(), 171);
+        assert_eq!(atsp.matrix.dimension(), 171);
+        assert_eq!(atsp.matrix.row(0).count(), 171);
+    }
+
+    #[test]
+    fn read_rejects_truncated_matrix() {
+        let path = std::env::temp_dir().join("evo_bio_truncated.atsp");
+        std::fs::write(
+            &path,
+            "NAME: t\nDIMENSION: 3\nEDGE_WEIGHT_SECTION\n0 1 2\n3 0 4\nEOF\n",
+        )
+        .unwrap();
+        let error = ATSP::<i32>::read_from_file(path.to_str().unwrap())
+            .err()
+            .unwrap();
+        assert_eq!(error.to_string(), MyError::MatrixSizeMismatch.to_string());
+    }
+
+    #[test]
+    fn narrow_matrix_keeps_cost() {
+        let mut atsp: ATSP<i32> =
+            ATSP::read_from_file(&format!("{}/rbg443.atsp", DATA_PATH)).unwrap();
+        let solution = Solution {
+            dimension: 443,
+            order: (0..443).collect(),
+        };
+        let wide_cost = atsp.cost_of_solution(&solution);
+        assert!(atsp.narrow_matrix());
+        assert_eq!(atsp.cost_of_solution(&solution), wide_cost);
     }
 
     #[test]
     fn cost_of_solution_small_instance() {
-        let atsp = ATSP::read_from_file(&format!("{}/br17.atsp", DATA_PATH)).unwrap();
+        let atsp: ATSP<i32> = ATSP::read_from_file(&format!("{}/br17.atsp", DATA_PATH)).unwrap();
         let solution = Solution {
             dimension: 17,
             order: (0..17).collect(),
@@ -147,7 +203,7 @@ mod tests {
 
     #[test]
     fn cost_of_solution_medium_instance() {
-        let atsp = ATSP::read_from_file(&format!("{}/p43.atsp", DATA_PATH)).unwrap();
+        let atsp: ATSP<i32> = ATSP::read_from_file(&format!("{}/p43.atsp", DATA_PATH)).unwrap();
         let solution = Solution {
             dimension: 43,
             order: (0..43).collect(),
@@ -157,7 +213,7 @@ mod tests {
 
     #[test]
     fn cost_of_solution_medium_instance_custom_order() {
-        let atsp = ATSP::read_from_file(&format!("{}/p43.atsp", DATA_PATH)).unwrap();
+        let atsp: ATSP<i32> = ATSP::read_from_file(&format!("{}/p43.atsp", DATA_PATH)).unwrap();
         let solution = Solution {
             dimension: 43,
             order: vec![
@@ -167,4 +223,19 @@ mod tests {
         };
         assert_eq!(atsp.cost_of_solution(&solution), 5620);
     }
+
+    #[test]
+    fn cost_of_solution_is_the_same_for_all_cost_types() {
+        let path = format!("{}/ftv170.atsp", DATA_PATH);
+        let solution = Solution {
+            dimension: 171,
+            order: (0..171).collect(),
+        };
+        let narrow: ATSP<i32> = ATSP::read_from_file(&path).unwrap();
+        let wide: ATSP<i64> = ATSP::read_from_file(&path).unwrap();
+        let float: ATSP<f64> = ATSP::read_from_file(&path).unwrap();
+        let expected = narrow.cost_of_solution(&solution);
+        assert_eq!(wide.cost_of_solution(&solution), expected as i64);
+        assert_eq!(float.cost_of_solution(&solution), expected as f64);
+    }
 }
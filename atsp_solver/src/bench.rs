@@ -0,0 +1,288 @@
+use std::collections::{BTreeMap, HashSet};
+use std::ffi::OsString;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::args;
+
+fn one() -> u32 {
+    1
+}
+
+/// Experiment grid in the format of scripts/configs. Unknown keys are ignored.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchConfig {
+    /// Time limit in nanoseconds of every instance, non-positive for none
+    pub instance_times: BTreeMap<String, i64>,
+    /// Algorithms, optionally followed by their initializer as in `tabu-search:nearest-neighbor`
+    pub algorithms: Vec<String>,
+    pub output_folder: String,
+    pub instances_folder: String,
+    pub repeats: u64,
+    pub start_seed: u64,
+    #[serde(default = "one")]
+    pub node_swap: u32,
+    #[serde(default = "one")]
+    pub edge_swap: u32,
+    /// Every combination of the values is run
+    #[serde(default)]
+    pub grid_params: BTreeMap<String, Vec<Value>>,
+    /// Do not measure the time of the runs with `--time`
+    #[serde(default)]
+    pub skip_time_check: bool,
+}
+
+/// A single run of the grid.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunSpec {
+    pub seed: u64,
+    pub algorithm: String,
+    pub instance: String,
+    pub time_ns: i64,
+    pub params: Vec<(String, Value)>,
+}
+
+impl RunSpec {
+    /// Runs in the order of the original script: repeats, algorithms, instances, parameters.
+    pub fn from_config(config: &BenchConfig) -> Vec<RunSpec> {
+        let mut grid: Vec<Vec<(String, Value)>> = vec![vec![]];
+        for (key, values) in config.grid_params.iter() {
+            grid = grid
+                .into_iter()
+                .flat_map(|params| {
+                    values.iter().map(move |value| {
+                        let mut params = params.clone();
+                        params.push((key.clone(), value.clone()));
+                        params
+                    })
+                })
+                .collect();
+        }
+
+        let mut specs = Vec::new();
+        for rep in 0..config.repeats {
+            for algorithm in config.algorithms.iter() {
+                for (instance, &time_ns) in config.instance_times.iter() {
+                    for params in grid.iter() {
+                        specs.push(RunSpec {
+                            seed: config.start_seed + rep,
+                            algorithm: algorithm.clone(),
+                            instance: instance.clone(),
+                            time_ns,
+                            params: params.clone(),
+                        });
+                    }
+                }
+            }
+        }
+        specs
+    }
+
+    /// Identifies the run in the results file.
+    pub fn id(&self) -> String {
+        let params: Vec<String> = self
+            .params
+            .iter()
+            .map(|(key, value)| format!("{}={}", key.trim_start_matches('-'), value))
+            .collect();
+        format!(
+            "{}_{}_{}_{}_{}",
+            self.seed,
+            self.algorithm,
+            self.instance,
+            self.time_ns,
+            params.join(",")
+        )
+    }
+
+    /// Solver options of the run.
+    pub fn options(&self, config: &BenchConfig) -> Result<args::Opt, String> {
+        let instance = Path::new(&config.instances_folder).join(format!("{}.atsp", self.instance));
+        let mut extra: Vec<OsString> = vec![
+            format!("--node-swap={}", config.node_swap).into(),
+            format!("--edge-swap={}", config.edge_swap).into(),
+        ];
+        if self.time_ns > 0 {
+            extra.push(format!("--max-time-ns={}", self.time_ns).into());
+        }
+        if !config.skip_time_check {
+            extra.push("--time".into());
+        }
+        extra.extend(args::config_args(self.params.iter().cloned().collect())?);
+        solver_options(&self.algorithm, &instance, self.seed, extra)
+    }
+}
+
+/// Solver options of a run of `algorithm`, optionally followed by its initializer
+/// as in `tabu-search:nearest-neighbor`, with `extra` arguments on top.
+pub fn solver_options(
+    algorithm: &str,
+    instance: &Path,
+    seed: u64,
+    extra: Vec<OsString>,
+) -> Result<args::Opt, String> {
+    let (algorithm, init) = match algorithm.split_once(':') {
+        Some((algorithm, init)) => (algorithm, Some(init)),
+        None => (algorithm, None),
+    };
+    let mut cli: Vec<OsString> = vec![
+        "atsp".into(),
+        format!("--instance={}", instance.display()).into(),
+        format!("--algorithm={}", algorithm).into(),
+        format!("--seed={}", seed).into(),
+    ];
+    if let Some(init) = init {
+        cli.push(format!("--init={}", init).into());
+    }
+    cli.extend(extra);
+    args::Opt::resolve(cli).map_err(|e| e.to_string().trim_end().to_string())
+}
+
+/// Ids of the runs already in the results file.
+fn completed_runs(path: &Path) -> HashSet<String> {
+    let Ok(text) = fs::read_to_string(path) else {
+        return HashSet::new();
+    };
+    text.lines()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+        .filter_map(|result| result.get("run")?.as_str().map(String::from))
+        .collect()
+}
+
+/// Runs the grid of the config, appending every finished run to the results file.
+pub fn run(opt: &args::BenchOpt) -> Result<(), Box<dyn std::error::Error>> {
+    let config: BenchConfig = args::read_config_file(&opt.config)?;
+    fs::create_dir_all(&config.output_folder)?;
+    let path = Path::new(&config.output_folder).join(format!("{}.jsonl", opt.output));
+
+    let specs = RunSpec::from_config(&config);
+    let completed = completed_runs(&path);
+    let pending: Vec<RunSpec> = specs
+        .iter()
+        .filter(|spec| !completed.contains(&spec.id()))
+        .cloned()
+        .collect();
+    let jobs = opt.jobs.map_or_else(
+        || std::thread::available_parallelism().map_or(1, |n| n.get()),
+        |jobs| jobs as usize,
+    );
+    eprintln!(
+        "Running {} of {} runs on {} jobs, {} already in {}",
+        pending.len(),
+        specs.len(),
+        jobs,
+        specs.len() - pending.len(),
+        path.display()
+    );
+
+    let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+    // A run interrupted while writing leaves a partial line behind
+    if fs::read(&path)?.last().is_some_and(|&byte| byte != b'\n') {
+        writeln!(file)?;
+    }
+    let file = Mutex::new(file);
+    let next = AtomicUsize::new(0);
+    let finished = AtomicUsize::new(0);
+    let failures = AtomicUsize::new(0);
+    std::thread::scope(|scope| {
+        for _ in 0..jobs.min(pending.len()) {
+            scope.spawn(|| loop {
+                let idx = next.fetch_add(1, Ordering::Relaxed);
+                let Some(spec) = pending.get(idx) else {
+                    break;
+                };
+                let id = spec.id();
+                let outcome = spec.options(&config).and_then(|options| {
+                    let result = crate::run_solver(&options).map_err(|e| e.to_string())?;
+                    let mut result: Value =
+                        serde_json::from_str(&result).map_err(|e| e.to_string())?;
+                    result["run"] = Value::String(id.clone());
+                    let mut file = file.lock().unwrap();
+                    writeln!(file, "{}", result)
+                        .and_then(|_| file.flush())
+                        .map_err(|e| e.to_string())
+                });
+                let done = finished.fetch_add(1, Ordering::Relaxed) + 1;
+                match outcome {
+                    Ok(()) => eprintln!("[{}/{}] {}", done, pending.len(), id),
+                    Err(error) => {
+                        failures.fetch_add(1, Ordering::Relaxed);
+                        eprintln!("[{}/{}] {} failed: {}", done, pending.len(), id, error);
+                    }
+                }
+            });
+        }
+    });
+
+    match failures.into_inner() {
+        0 => Ok(()),
+        failed => Err(format!(
+            "{} runs failed and will be retried on the next start",
+            failed
+        )
+        .into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(grid_params: &str) -> BenchConfig {
+        serde_json::from_str(&format!(
+            r#"{{
+                "instanceTimes": {{"br17": -1, "ftv33": 1000000}},
+                "algorithms": ["random", "tabu-search:nearest-neighbor"],
+                "repeats": 2,
+                "startSeed": 5,
+                "outputFolder": "results",
+                "instancesFolder": "../data/ALL_atsp",
+                "cmdBaseArgs": "run --release --",
+                "gridParams": {}
+            }}"#,
+            grid_params
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn grid_covers_every_combination() {
+        let config = config(r#"{"--tabu-patience": [2, 5, 10], "--tabu-elite": [0.05, 0.1]}"#);
+        let specs = RunSpec::from_config(&config);
+        assert_eq!(specs.len(), 2 * 2 * 2 * 6);
+        let ids: HashSet<String> = specs.iter().map(RunSpec::id).collect();
+        assert_eq!(ids.len(), specs.len());
+        assert_eq!(specs[0].seed, 5);
+        assert_eq!(specs.last().unwrap().seed, 6);
+    }
+
+    #[test]
+    fn run_options_follow_the_spec() {
+        let config = config(r#"{"--tabu-patience": [7]}"#);
+        let spec = RunSpec::from_config(&config)
+            .into_iter()
+            .find(|spec| spec.algorithm.contains(':') && spec.time_ns > 0)
+            .unwrap();
+        let options = spec.options(&config).unwrap();
+        assert!(matches!(options.algorithm, args::Algorithm::TabuSearch));
+        assert!(matches!(options.init, args::Init::NearestNeighbor));
+        assert_eq!(options.tabu_patience, 7);
+        assert_eq!(options.max_time_ns, 1000000);
+        assert!(options.time);
+        assert!(options.instance.ends_with("ftv33.atsp"));
+    }
+
+    #[test]
+    fn invalid_parameters_are_reported() {
+        let config = config(r#"{"--tabu-elite": [2.0]}"#);
+        let spec = &RunSpec::from_config(&config)[0];
+        assert!(spec.options(&config).is_err());
+    }
+}
@@ -0,0 +1,58 @@
+/// Best known tour costs of the instances, from data/atsp-sol.html.
+const BEST_KNOWN: [(&str, i64); 27] = [
+    ("br17", 39),
+    ("ft53", 6905),
+    ("ft70", 38673),
+    ("ftv33", 1286),
+    ("ftv35", 1473),
+    ("ftv38", 1530),
+    ("ftv44", 1613),
+    ("ftv47", 1776),
+    ("ftv55", 1608),
+    ("ftv64", 1839),
+    ("ftv70", 1950),
+    ("ftv90", 1579),
+    ("ftv100", 1788),
+    ("ftv110", 1958),
+    ("ftv120", 2166),
+    ("ftv130", 2307),
+    ("ftv140", 2420),
+    ("ftv150", 2611),
+    ("ftv160", 2683),
+    ("ftv170", 2755),
+    ("kro124p", 36230),
+    ("p43", 5620),
+    ("rbg323", 1326),
+    ("rbg358", 1163),
+    ("rbg403", 2465),
+    ("rbg443", 2720),
+    ("ry48p", 14422),
+];
+
+pub fn best_known_cost(instance: &str) -> Option<i64> {
+    BEST_KNOWN
+        .iter()
+        .find(|(name, _)| *name == instance)
+        .map(|&(_, cost)| cost)
+}
+
+/// Relative distance of the cost above the best known cost, 0 for an optimal tour.
+pub fn relative_gap(cost: f64, best_known: f64) -> f64 {
+    (cost - best_known) / best_known
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::atsp::ATSP;
+
+    #[test]
+    fn every_instance_has_a_best_known_cost() {
+        for entry in std::fs::read_dir("../data/ALL_atsp").unwrap() {
+            let path = entry.unwrap().path();
+            let atsp: ATSP<i64> = ATSP::read_from_file(path.to_str().unwrap()).unwrap();
+            assert!(best_known_cost(&atsp.name).is_some(), "{}", atsp.name);
+        }
+        assert_eq!(relative_gap(1350.0, 1286.0), 64.0 / 1286.0);
+    }
+}
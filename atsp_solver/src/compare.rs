@@ -0,0 +1,669 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use serde_json::Value;
+
+use evo_bio::best_known::{best_known_cost, relative_gap};
+use evo_bio::export::RunResult;
+use evo_bio::multistart::Summary;
+use evo_bio::{stats, utils};
+
+use crate::args;
+
+/// Reads the results of a JSON file holding a result or an array of them, a JSON Lines file
+/// or every such file of a folder. Entries of another schema version are skipped like malformed
+/// ones.
+fn read_results(path: &Path) -> Result<Vec<RunResult<f64>>, String> {
+    let read_error = |e: std::io::Error| format!("{}: {}", path.display(), e);
+    if path.is_dir() {
+        let mut entries: Vec<_> = fs::read_dir(path)
+            .map_err(read_error)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|ext| ext == "json" || ext == "jsonl")
+            })
+            .collect();
+        entries.sort();
+        let mut results = Vec::new();
+        for entry in entries {
+            results.extend(read_results(&entry)?);
+        }
+        return Ok(results);
+    }
+    let text = fs::read_to_string(path).map_err(read_error)?;
+    let parse_error = |e: serde_json::Error| format!("{}: {}", path.display(), e);
+    let mut results = Vec::new();
+    let mut skipped = Vec::new();
+    let entries: Vec<(usize, String)> = if path.extension().is_some_and(|ext| ext == "jsonl") {
+        // A bench interrupted while writing leaves a partial last line behind
+        text.lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(idx, line)| (idx + 1, line.to_string()))
+            .collect()
+    } else {
+        match serde_json::from_str(&text).map_err(parse_error)? {
+            // Numbered by their position in the array
+            Value::Array(entries) => entries
+                .iter()
+                .enumerate()
+                .map(|(idx, entry)| (idx + 1, entry.to_string()))
+                .collect(),
+            _ => return Ok(vec![RunResult::from_json(&text).map_err(parse_error)?]),
+        }
+    };
+    for (number, entry) in entries {
+        match RunResult::from_json(&entry) {
+            Ok(result) => results.push(result),
+            Err(e) => skipped.push(format!("{} ({})", number, e)),
+        }
+    }
+    if !skipped.is_empty() {
+        eprintln!(
+            "{}: skipped {} malformed results: {}",
+            path.display(),
+            skipped.len(),
+            skipped.join(", ")
+        );
+    }
+    Ok(results)
+}
+
+/// Looks `key` up in the hyperparameters of the result, its config, then its other fields.
+fn field(result: &RunResult<f64>, key: &str) -> Option<Value> {
+    if let Some(&value) = result.hyperparameters.get(key) {
+        return Some(Value::from(value));
+    }
+    result
+        .config
+        .get(key)
+        .cloned()
+        .or_else(|| serde_json::to_value(result).ok()?.get(key).cloned())
+}
+
+/// Name of the compared algorithm: the method, its initializer and neighborhood
+/// followed by the values of the `by` keys.
+fn group_name(result: &RunResult<f64>, by: &[String]) -> String {
+    let mut name = result.method.clone();
+    for (key, value) in [
+        ("init", &result.init),
+        ("neighborhood", &result.neighborhood),
+    ] {
+        // Left empty by the runs that do not set them
+        if !value.is_empty() {
+            name.push_str(&format!(" {}={}", key, value));
+        }
+    }
+    for key in by
+        .iter()
+        .filter(|key| !["init", "neighborhood"].contains(&key.as_str()))
+    {
+        match field(result, key) {
+            Some(Value::String(value)) => name.push_str(&format!(" {}={}", key, value)),
+            Some(value) => name.push_str(&format!(" {}={}", key, value)),
+            None => name.push_str(&format!(" {}=-", key)),
+        }
+    }
+    name
+}
+
+struct Run {
+    cost: f64,
+    /// Negative when the run was not timed
+    time: f64,
+    evaluations: f64,
+}
+
+/// Runs of an algorithm on an instance.
+struct Cell {
+    runs: usize,
+    gap: Summary,
+    time: Option<f64>,
+    evaluations: f64,
+}
+
+struct Pair {
+    first: usize,
+    second: usize,
+    test: stats::Wilcoxon,
+    adjusted_p_value: f64,
+}
+
+struct Ranking {
+    test: stats::Friedman,
+    instances: usize,
+    critical_difference: f64,
+}
+
+struct Comparison {
+    instances: Vec<String>,
+    groups: Vec<String>,
+    cells: BTreeMap<(usize, usize), Cell>,
+    ranking: Option<Ranking>,
+    pairs: Vec<Pair>,
+    alpha: f64,
+}
+
+impl Comparison {
+    fn new(results: &[RunResult<f64>], by: &[String], alpha: f64) -> Result<Comparison, String> {
+        let mut runs: BTreeMap<(String, String), Vec<Run>> = BTreeMap::new();
+        for result in results {
+            runs.entry((result.instance.clone(), group_name(result, by)))
+                .or_default()
+                .push(Run {
+                    cost: result.cost,
+                    time: result.time,
+                    evaluations: result.evaluations as f64,
+                });
+        }
+        if runs.is_empty() {
+            return Err("No results to compare".to_string());
+        }
+
+        let mut instances: Vec<String> = runs.keys().map(|(i, _)| i.clone()).collect();
+        instances.dedup();
+        let mut groups: Vec<String> = runs.keys().map(|(_, g)| g.clone()).collect();
+        groups.sort();
+        groups.dedup();
+
+        let mut cells = BTreeMap::new();
+        for (instance_idx, instance) in instances.iter().enumerate() {
+            let best_known = best_known_cost(instance).map(|cost| cost as f64);
+            let best_known = best_known.unwrap_or_else(|| {
+                eprintln!(
+                    "No best known cost of {}, gaps are relative to the best result",
+                    instance
+                );
+                runs.iter()
+                    .filter(|((i, _), _)| i == instance)
+                    .flat_map(|(_, runs)| runs.iter().map(|run| run.cost))
+                    .fold(f64::INFINITY, f64::min)
+            });
+            for (group_idx, group) in groups.iter().enumerate() {
+                let Some(runs) = runs.get(&(instance.clone(), group.clone())) else {
+                    continue;
+                };
+                let gaps: Vec<f64> = runs
+                    .iter()
+                    .map(|run| relative_gap(run.cost, best_known))
+                    .collect();
+                let times: Vec<f64> = runs
+                    .iter()
+                    .map(|run| run.time)
+                    .filter(|&t| t >= 0.0)
+                    .collect();
+                cells.insert(
+                    (instance_idx, group_idx),
+                    Cell {
+                        runs: runs.len(),
+                        gap: Summary::from_values(&gaps),
+                        time: (!times.is_empty())
+                            .then(|| times.iter().sum::<f64>() / times.len() as f64),
+                        evaluations: runs.iter().map(|run| run.evaluations).sum::<f64>()
+                            / runs.len() as f64,
+                    },
+                );
+            }
+        }
+
+        let mean_gaps = |group: usize, instances: &[usize]| -> Vec<f64> {
+            instances
+                .iter()
+                .map(|&i| cells[&(i, group)].gap.mean)
+                .collect()
+        };
+        // Rankings need every algorithm on every instance
+        let complete: Vec<usize> = (0..instances.len())
+            .filter(|&i| (0..groups.len()).all(|g| cells.contains_key(&(i, g))))
+            .collect();
+        let ranking = (groups.len() > 1 && !complete.is_empty()).then(|| {
+            let blocks: Vec<Vec<f64>> = complete
+                .iter()
+                .map(|&i| (0..groups.len()).map(|g| cells[&(i, g)].gap.mean).collect())
+                .collect();
+            Ranking {
+                test: stats::friedman(&blocks),
+                instances: complete.len(),
+                critical_difference: stats::critical_difference(
+                    groups.len(),
+                    complete.len(),
+                    alpha,
+                ),
+            }
+        });
+
+        let mut pairs = Vec::new();
+        for first in 0..groups.len() {
+            for second in first + 1..groups.len() {
+                let shared: Vec<usize> = (0..instances.len())
+                    .filter(|&i| {
+                        cells.contains_key(&(i, first)) && cells.contains_key(&(i, second))
+                    })
+                    .collect();
+                if shared.is_empty() {
+                    continue;
+                }
+                pairs.push(Pair {
+                    first,
+                    second,
+                    test: stats::wilcoxon(&mean_gaps(first, &shared), &mean_gaps(second, &shared)),
+                    adjusted_p_value: 1.0,
+                });
+            }
+        }
+        let p_values: Vec<f64> = pairs.iter().map(|pair| pair.test.p_value).collect();
+        for (pair, p) in pairs.iter_mut().zip(stats::holm(&p_values)) {
+            pair.adjusted_p_value = p;
+        }
+
+        Ok(Comparison {
+            instances,
+            groups,
+            cells,
+            ranking,
+            pairs,
+            alpha,
+        })
+    }
+
+    /// Gap and cost statistics of every algorithm on every instance.
+    fn instance_table(&self, raw: bool) -> Table {
+        let percent = |value: f64| {
+            if raw {
+                value.to_string()
+            } else {
+                format!("{:.3}%", 100.0 * value)
+            }
+        };
+        let mut table = Table::new(&[
+            "instance",
+            "algorithm",
+            "runs",
+            "mean gap",
+            "median gap",
+            "best gap",
+            "mean time",
+            "mean evaluations",
+        ]);
+        for (&(instance, group), cell) in self.cells.iter() {
+            let time = match cell.time {
+                Some(time) if raw => time.to_string(),
+                Some(time) => utils::humanize_time(time),
+                None => String::new(),
+            };
+            table.rows.push(vec![
+                self.instances[instance].clone(),
+                self.groups[group].clone(),
+                cell.runs.to_string(),
+                percent(cell.gap.mean),
+                percent(cell.gap.median),
+                percent(cell.gap.min),
+                time,
+                if raw {
+                    cell.evaluations.to_string()
+                } else {
+                    format!("{:.0}", cell.evaluations)
+                },
+            ]);
+        }
+        table
+    }
+
+    fn ranking_table(&self, raw: bool) -> Option<Table> {
+        let ranking = self.ranking.as_ref()?;
+        let best = ranking
+            .test
+            .mean_ranks
+            .iter()
+            .copied()
+            .fold(f64::INFINITY, f64::min);
+        let mut order: Vec<usize> = (0..self.groups.len()).collect();
+        order.sort_by(|&a, &b| {
+            ranking.test.mean_ranks[a]
+                .partial_cmp(&ranking.test.mean_ranks[b])
+                .unwrap()
+        });
+        let mut table = Table::new(&["algorithm", "mean rank", "within CD of the best"]);
+        for group in order {
+            let rank = ranking.test.mean_ranks[group];
+            table.rows.push(vec![
+                self.groups[group].clone(),
+                if raw {
+                    rank.to_string()
+                } else {
+                    format!("{:.3}", rank)
+                },
+                if rank - best <= ranking.critical_difference {
+                    "yes"
+                } else {
+                    "no"
+                }
+                .to_string(),
+            ]);
+        }
+        Some(table)
+    }
+
+    fn ranking_summary(&self) -> Option<String> {
+        let ranking = self.ranking.as_ref()?;
+        Some(format!(
+            "Friedman test on {} instances: chi2 = {:.3}, p = {:.4}, critical difference = {:.3} (alpha {})",
+            ranking.instances,
+            ranking.test.statistic,
+            ranking.test.p_value,
+            ranking.critical_difference,
+            self.alpha
+        ))
+    }
+
+    fn pair_table(&self, raw: bool) -> Table {
+        let p_value = |p: f64| {
+            if raw {
+                p.to_string()
+            } else {
+                format!("{:.4}", p)
+            }
+        };
+        let mut table = Table::new(&["first", "second", "instances", "W", "p", "Holm p", "better"]);
+        for pair in self.pairs.iter() {
+            let shared: Vec<usize> = (0..self.instances.len())
+                .filter(|&i| {
+                    self.cells.contains_key(&(i, pair.first))
+                        && self.cells.contains_key(&(i, pair.second))
+                })
+                .collect();
+            let mean = |group: usize| {
+                shared
+                    .iter()
+                    .map(|&i| self.cells[&(i, group)].gap.mean)
+                    .sum::<f64>()
+            };
+            let better = if pair.adjusted_p_value >= self.alpha {
+                "-"
+            } else if mean(pair.first) < mean(pair.second) {
+                &self.groups[pair.first]
+            } else {
+                &self.groups[pair.second]
+            };
+            table.rows.push(vec![
+                self.groups[pair.first].clone(),
+                self.groups[pair.second].clone(),
+                shared.len().to_string(),
+                format!("{}", pair.test.statistic),
+                p_value(pair.test.p_value),
+                p_value(pair.adjusted_p_value),
+                better.to_string(),
+            ]);
+        }
+        table
+    }
+
+    fn text(&self) -> String {
+        let mut text = self.instance_table(false).text();
+        if let (Some(summary), Some(table)) = (self.ranking_summary(), self.ranking_table(false)) {
+            text.push_str(&format!("\n{}\n{}", summary, table.text()));
+        }
+        if !self.pairs.is_empty() {
+            text.push_str("\nWilcoxon signed-rank tests on the mean gaps of the instances\n");
+            text.push_str(&self.pair_table(false).text());
+        }
+        text
+    }
+
+    fn markdown(&self) -> String {
+        let mut text = format!("## Instances\n\n{}", self.instance_table(false).markdown());
+        if let (Some(summary), Some(table)) = (self.ranking_summary(), self.ranking_table(false)) {
+            text.push_str(&format!(
+                "\n## Ranking\n\n{}\n\n{}",
+                summary,
+                table.markdown()
+            ));
+        }
+        if !self.pairs.is_empty() {
+            text.push_str(&format!(
+                "\n## Pairwise tests\n\nWilcoxon signed-rank tests on the mean gaps of the instances\n\n{}",
+                self.pair_table(false).markdown()
+            ));
+        }
+        text
+    }
+}
+
+struct Table {
+    header: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    fn new(header: &[&str]) -> Table {
+        Table {
+            header: header.iter().map(|h| h.to_string()).collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    /// Columns padded to their widest value, text aligned left and numbers right.
+    fn text(&self) -> String {
+        let widths: Vec<usize> = (0..self.header.len())
+            .map(|col| {
+                self.rows
+                    .iter()
+                    .map(|row| row[col].len())
+                    .chain([self.header[col].len()])
+                    .max()
+                    .unwrap()
+            })
+            .collect();
+        let numeric: Vec<bool> = (0..self.header.len())
+            .map(|col| {
+                self.rows.iter().all(|row| {
+                    row[col].is_empty()
+                        || row[col].starts_with(|c: char| c.is_ascii_digit() || c == '-')
+                })
+            })
+            .collect();
+        let line = |row: &[String]| {
+            let cells: Vec<String> = row
+                .iter()
+                .enumerate()
+                .map(|(col, cell)| {
+                    if numeric[col] {
+                        format!("{:>width$}", cell, width = widths[col])
+                    } else {
+                        format!("{:<width$}", cell, width = widths[col])
+                    }
+                })
+                .collect();
+            cells.join("  ").trim_end().to_string() + "\n"
+        };
+        let mut text = line(&self.header);
+        for row in self.rows.iter() {
+            text.push_str(&line(row));
+        }
+        text
+    }
+
+    fn markdown(&self) -> String {
+        let line = |row: &[String]| format!("| {} |\n", row.join(" | "));
+        let mut text = line(&self.header);
+        text.push_str(&line(&vec!["---".to_string(); self.header.len()]));
+        for row in self.rows.iter() {
+            text.push_str(&line(row));
+        }
+        text
+    }
+
+    fn csv(&self) -> String {
+        let escape = |cell: &String| {
+            if cell.contains([',', '"', '\n']) {
+                format!("\"{}\"", cell.replace('"', "\"\""))
+            } else {
+                cell.clone()
+            }
+        };
+        let line = |row: &[String]| row.iter().map(escape).collect::<Vec<_>>().join(",") + "\n";
+        let mut text = line(&self.header);
+        for row in self.rows.iter() {
+            text.push_str(&line(row));
+        }
+        text
+    }
+}
+
+/// Compares the algorithms of the results, printing the report and writing it as CSV and Markdown.
+/// The instance statistics go to `<output>.csv`, the ranking to `<output>_ranking.csv` and
+/// the pairwise tests to `<output>_pairs.csv`.
+pub fn run(opt: &args::CompareOpt) -> Result<(), Box<dyn std::error::Error>> {
+    let mut results = Vec::new();
+    for path in opt.results.iter() {
+        results.extend(read_results(Path::new(path))?);
+    }
+    let comparison = Comparison::new(&results, &opt.by, opt.alpha)?;
+    print!("{}", comparison.text());
+    if let Some(output) = &opt.output {
+        fs::write(
+            format!("{}.csv", output),
+            comparison.instance_table(true).csv(),
+        )?;
+        if let Some(table) = comparison.ranking_table(true) {
+            fs::write(format!("{}_ranking.csv", output), table.csv())?;
+        }
+        if !comparison.pairs.is_empty() {
+            fs::write(
+                format!("{}_pairs.csv", output),
+                comparison.pair_table(true).csv(),
+            )?;
+        }
+        fs::write(format!("{}.md", output), comparison.markdown())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use evo_bio::export::SCHEMA_VERSION;
+    use serde_json::json;
+
+    fn result(method: &str, instance: &str, cost: f64, alpha: f64) -> RunResult<f64> {
+        RunResult::from_json(&result_json(method, instance, cost, alpha).to_string()).unwrap()
+    }
+
+    fn result_json(method: &str, instance: &str, cost: f64, alpha: f64) -> Value {
+        json!({
+            "schema_version": SCHEMA_VERSION,
+            "solver_version": "0.1.0",
+            "timestamp": 0,
+            "instance": instance,
+            "method": method,
+            "init": "",
+            "neighborhood": "",
+            "seed": 0,
+            "max_time_ns": -1,
+            "order": [],
+            "cost": cost,
+            "initial_cost": cost,
+            "time": -1,
+            "iterations": 10,
+            "steps": 10,
+            "evaluations": 100,
+            "evaluations_history": [],
+            "cost_history": [],
+            "hyperparameters": {"sa-alpha": alpha},
+            "config": {},
+        })
+    }
+
+    #[test]
+    fn results_are_grouped_and_ranked() {
+        let mut results = Vec::new();
+        for (instance, best) in [("ftv33", 1286.0), ("ftv44", 1613.0), ("p43", 5620.0)] {
+            for seed in 0..3 {
+                let seed = seed as f64;
+                results.push(result("simulated-annealing", instance, best + seed, 0.9));
+                results.push(result(
+                    "simulated-annealing",
+                    instance,
+                    best * 1.1 + seed,
+                    0.5,
+                ));
+                results.push(result("random", instance, best * 2.0 + seed, 0.0));
+            }
+        }
+        let by = vec!["sa-alpha".to_string()];
+        let comparison = Comparison::new(&results, &by, 0.05).unwrap();
+        assert_eq!(comparison.instances, vec!["ftv33", "ftv44", "p43"]);
+        assert_eq!(comparison.groups.len(), 3);
+        let best = comparison
+            .groups
+            .iter()
+            .position(|g| g == "simulated-annealing sa-alpha=0.9")
+            .unwrap();
+        let cell = &comparison.cells[&(0, best)];
+        assert_eq!((cell.runs, cell.gap.min), (3, 0.0));
+        assert!(cell.time.is_none());
+        let ranking = comparison.ranking.as_ref().unwrap();
+        assert_eq!(ranking.test.mean_ranks[best], 1.0);
+        // Three instances are too few for the signed-rank test to be significant
+        assert_eq!(comparison.pairs.len(), 3);
+        assert!(comparison
+            .pairs
+            .iter()
+            .all(|pair| pair.test.p_value == 0.25));
+
+        let markdown = comparison.markdown();
+        assert!(markdown.contains("| ftv33 | random sa-alpha=0.0 | 3 | 100.078% |"));
+        let csv = comparison.instance_table(true).csv();
+        assert_eq!(csv.lines().count(), 1 + 9);
+        let csv = comparison.ranking_table(true).unwrap().csv();
+        assert!(csv.contains("\nsimulated-annealing sa-alpha=0.9,1,yes\n"));
+        let csv = comparison.pair_table(true).csv();
+        assert_eq!(csv.lines().count(), 1 + 3);
+        assert!(csv.lines().skip(1).all(|line| line.contains(",0.25,")));
+    }
+
+    #[test]
+    fn initializers_and_neighborhoods_are_told_apart() {
+        let mut results = Vec::new();
+        for init in ["random", "nearest-neighbor"] {
+            let mut result = result("tabu-search", "ftv33", 1300.0, 0.0);
+            result.init = init.to_string();
+            result.neighborhood = "edge-swap".to_string();
+            results.push(result);
+        }
+        let by = vec!["init".to_string()];
+        let comparison = Comparison::new(&results, &by, 0.05).unwrap();
+        assert_eq!(
+            comparison.groups,
+            vec![
+                "tabu-search init=nearest-neighbor neighborhood=edge-swap",
+                "tabu-search init=random neighborhood=edge-swap"
+            ]
+        );
+    }
+
+    #[test]
+    fn malformed_and_other_version_results_are_skipped() {
+        let path = std::env::temp_dir().join("evo_bio_compare.jsonl");
+        let mut line = result_json("random", "ftv33", 2000.0, 0.0);
+        line["run"] = json!(3);
+        let mut old = line.clone();
+        old["schema_version"] = json!(1);
+        fs::write(
+            &path,
+            format!("{}\n{{\"cost\n\n{}\n{}\n{{", line, old, line),
+        )
+        .unwrap();
+        assert_eq!(read_results(&path).unwrap().len(), 2);
+
+        let path = std::env::temp_dir().join("evo_bio_compare.json");
+        fs::write(&path, json!([line, old]).to_string()).unwrap();
+        assert_eq!(read_results(&path).unwrap().len(), 1);
+        fs::write(&path, old.to_string()).unwrap();
+        assert!(read_results(&path)
+            .unwrap_err()
+            .contains("schema version 1, expected version 2"));
+    }
+}
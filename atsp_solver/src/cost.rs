@@ -0,0 +1,124 @@
+use std::fmt::{Debug, Display};
+use std::ops::{Add, AddAssign, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Numeric type used for arc weights, tour costs and deltas.
+pub trait Cost:
+    Copy
+    + PartialOrd
+    + Debug
+    + Display
+    + FromStr
+    + Send
+    + Sync
+    + 'static
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Neg<Output = Self>
+    + AddAssign
+    + SubAssign
+    + Serialize
+    + DeserializeOwned
+{
+    const ZERO: Self;
+    const MAX: Self;
+
+    fn to_f64(self) -> f64;
+
+    fn from_f64(value: f64) -> Self;
+
+    fn to_u16(self) -> Option<u16>;
+
+    fn from_u16(value: u16) -> Self;
+
+    /// Weight substituted for arcs that could not be parsed.
+    /// Large enough to dominate any real arc, yet small enough that a tour
+    /// or a delta summing such arcs cannot overflow.
+    fn unreachable(dimension: usize) -> Self {
+        Self::from_f64(Self::MAX.to_f64() / (2.0 * (dimension as f64 + 2.0)))
+    }
+}
+
+macro_rules! impl_integer_cost {
+    ($($t:ty),*) => {$(
+        impl Cost for $t {
+            const ZERO: Self = 0;
+            const MAX: Self = <$t>::MAX;
+
+            #[inline(always)]
+            fn to_f64(self) -> f64 {
+                self as f64
+            }
+
+            #[inline(always)]
+            fn from_f64(value: f64) -> Self {
+                value.round() as $t
+            }
+
+            fn to_u16(self) -> Option<u16> {
+                u16::try_from(self).ok()
+            }
+
+            #[inline(always)]
+            fn from_u16(value: u16) -> Self {
+                value as $t
+            }
+        }
+    )*};
+}
+
+impl_integer_cost!(i32, i64);
+
+impl Cost for f64 {
+    const ZERO: Self = 0.0;
+    const MAX: Self = f64::MAX;
+
+    #[inline(always)]
+    fn to_f64(self) -> f64 {
+        self
+    }
+
+    #[inline(always)]
+    fn from_f64(value: f64) -> Self {
+        value
+    }
+
+    fn to_u16(self) -> Option<u16> {
+        if self.fract() == 0.0 && (0.0..=u16::MAX as f64).contains(&self) {
+            Some(self as u16)
+        } else {
+            None
+        }
+    }
+
+    #[inline(always)]
+    fn from_u16(value: u16) -> Self {
+        value as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unreachable_tour_does_not_overflow() {
+        let dimension = 443;
+        let arc = i32::unreachable(dimension);
+        let mut total = 0i32;
+        for _ in 0..dimension + 2 {
+            total = total.checked_add(arc).unwrap();
+        }
+        assert!(arc > 9999);
+    }
+
+    #[test]
+    fn float_narrowing_requires_integral_values() {
+        assert_eq!(3.0f64.to_u16(), Some(3));
+        assert_eq!(3.5f64.to_u16(), None);
+        assert_eq!((-1.0f64).to_u16(), None);
+    }
+}
@@ -1,11 +1,14 @@
-pub fn get_node_swap_delta(
+use crate::cost::Cost;
+use crate::matrix::ArcCosts;
+
+pub fn get_node_swap_delta<C: Cost, M: ArcCosts<C>>(
     solution: &Vec<u32>,
     mut first_idx: usize,
     mut second_idx: usize,
-    cost_matrix: &Vec<Vec<i32>>,
-) -> i32 {
+    cost_matrix: &M,
+) -> C {
     if first_idx == second_idx {
-        return 0;
+        return C::ZERO;
     }
     let n = solution.len();
     if first_idx > second_idx {
@@ -18,50 +21,49 @@ pub fn get_node_swap_delta(
     let second_prev = solution[(second_idx + n - 1) % n] as usize;
     let second_next = solution[(second_idx + 1) % n] as usize;
 
-    let mut delta = 0;
+    let mut delta = C::ZERO;
 
     if first_idx == 0 && second_idx == n - 1 {
-        delta += cost_matrix[first][second];
-        delta -= cost_matrix[second][first];
+        delta += cost_matrix.arc(first, second);
+        delta -= cost_matrix.arc(second, first);
 
-        delta += cost_matrix[second_prev][first];
-        delta -= cost_matrix[first][first_next];
+        delta += cost_matrix.arc(second_prev, first);
+        delta -= cost_matrix.arc(first, first_next);
 
-        delta += cost_matrix[second][first_next];
-        delta -= cost_matrix[second_prev][second];
+        delta += cost_matrix.arc(second, first_next);
+        delta -= cost_matrix.arc(second_prev, second);
 
         return delta;
     }
 
-    delta += cost_matrix[first_prev][second];
-    delta += cost_matrix[first][second_next];
+    delta += cost_matrix.arc(first_prev, second);
+    delta += cost_matrix.arc(first, second_next);
 
-    delta -= cost_matrix[first_prev][first];
-    delta -= cost_matrix[second][second_next];
+    delta -= cost_matrix.arc(first_prev, first);
+    delta -= cost_matrix.arc(second, second_next);
 
-    let idx_diff = second_idx as i32 - first_idx as i32;
-    if idx_diff.abs() == 1 {
-        delta -= cost_matrix[first][second];
-        delta += cost_matrix[second][first];
+    if second_idx - first_idx == 1 {
+        delta -= cost_matrix.arc(first, second);
+        delta += cost_matrix.arc(second, first);
         return delta;
     }
 
-    delta += cost_matrix[second][first_next];
-    delta += cost_matrix[second_prev][first];
-    delta -= cost_matrix[first][first_next];
-    delta -= cost_matrix[second_prev][second];
+    delta += cost_matrix.arc(second, first_next);
+    delta += cost_matrix.arc(second_prev, first);
+    delta -= cost_matrix.arc(first, first_next);
+    delta -= cost_matrix.arc(second_prev, second);
 
     return delta;
 }
 
-pub fn get_edge_swap_delta(
+pub fn get_edge_swap_delta<C: Cost, M: ArcCosts<C>>(
     solution: &Vec<u32>,
     mut first_idx: usize,
     mut second_idx: usize,
-    cost_matrix: &Vec<Vec<i32>>,
-) -> i32 {
+    cost_matrix: &M,
+) -> C {
     if first_idx == second_idx {
-        return 0;
+        return C::ZERO;
     }
     let n = solution.len();
     if (first_idx > second_idx) || (first_idx == 0 && second_idx == n - 1) {
@@ -73,22 +75,60 @@ pub fn get_edge_swap_delta(
     let second_next = solution[(second_idx + 1) % n] as usize;
 
     if first_next == second || second_next == first {
-        return 0;
+        return C::ZERO;
     }
 
-    let mut delta = 0;
-    delta += cost_matrix[first][second];
-    delta += cost_matrix[first_next][second_next];
+    let mut delta = C::ZERO;
+    delta += cost_matrix.arc(first, second);
+    delta += cost_matrix.arc(first_next, second_next);
 
-    delta -= cost_matrix[first][first_next];
-    delta -= cost_matrix[second][second_next];
+    delta -= cost_matrix.arc(first, first_next);
+    delta -= cost_matrix.arc(second, second_next);
 
     for i in (first_idx + 1)..second_idx {
         let i = i % n;
         let j = (i + 1) % n;
-        delta -= cost_matrix[solution[i] as usize][solution[j] as usize];
-        delta += cost_matrix[solution[j] as usize][solution[i] as usize];
+        delta -= cost_matrix.arc(solution[i] as usize, solution[j] as usize);
+        delta += cost_matrix.arc(solution[j] as usize, solution[i] as usize);
     }
 
     return delta;
 }
+
+/// Arc costs augmented with the penalties of guided local search.
+/// `penalties` holds the already scaled penalty of every arc, in row-major order.
+pub struct PenalizedCosts<'a, C: Cost, M: ArcCosts<C>> {
+    pub matrix: &'a M,
+    pub dimension: usize,
+    pub penalties: &'a [C],
+}
+
+impl<C: Cost, M: ArcCosts<C>> ArcCosts<C> for PenalizedCosts<'_, C, M> {
+    #[inline(always)]
+    fn arc(&self, from: usize, to: usize) -> C {
+        self.matrix.arc(from, to) + self.penalties[from * self.dimension + to]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matrix::CostMatrix;
+
+    #[test]
+    fn penalized_delta_includes_penalties() {
+        let matrix: CostMatrix<i32> = CostMatrix::new(4, vec![1; 16]).unwrap();
+        let mut penalties = vec![0; 16];
+        // Arc 1 -> 2 is removed by swapping the nodes at positions 1 and 2
+        penalties[4 + 2] = 5;
+        let costs = PenalizedCosts {
+            matrix: &matrix,
+            dimension: 4,
+            penalties: &penalties,
+        };
+        let order = vec![0, 1, 2, 3];
+        assert_eq!(get_node_swap_delta(&order, 1, 2, &matrix), 0);
+        assert_eq!(get_node_swap_delta(&order, 1, 2, &costs), -5);
+        assert_eq!(get_edge_swap_delta(&order, 0, 2, &costs), -5);
+    }
+}
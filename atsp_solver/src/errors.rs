@@ -6,6 +6,10 @@ pub enum MyError {
     OutOfRange,
     DimensionMismatch,
     LengthMismatch,
+    MatrixSizeMismatch,
+    TooFewCities,
+    TooManyCities,
+    WorkerPanicked,
 }
 
 impl fmt::Display for MyError {
@@ -14,6 +18,12 @@ impl fmt::Display for MyError {
             MyError::OutOfRange => write!(f, "Not all solution elements are in the valid range"),
             MyError::DimensionMismatch => write!(f, "Solution dimension does not match"),
             MyError::LengthMismatch => write!(f, "Solution length does not match dimension"),
+            MyError::MatrixSizeMismatch => {
+                write!(f, "Number of matrix values does not match the dimension")
+            }
+            MyError::TooFewCities => write!(f, "Instance must have at least 3 cities"),
+            MyError::TooManyCities => write!(f, "Instance has more cities than moves can encode"),
+            MyError::WorkerPanicked => write!(f, "A worker thread panicked"),
         }
     }
 }
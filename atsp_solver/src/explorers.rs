@@ -1,19 +1,24 @@
-use std::collections::VecDeque;
+use std::cmp::Ordering;
+use std::sync::Arc;
 
 use rand::Rng;
 
+use crate::acceptance::AcceptanceCriterion;
 use crate::atsp::ATSP;
+use crate::cost::Cost;
 use crate::operation;
-use crate::search::{Context, Explorer};
+use crate::operator_selection::{OperatorSelector, OperatorStats};
+use crate::search::{ChainStats, Context, Explorer};
 use crate::solution::Solution;
+use crate::tabu::TabuMemory;
 use crate::utils;
 
 pub struct PassThroughExplorer {}
 
-impl Explorer for PassThroughExplorer {
-    fn explore(&mut self, _: &ATSP, _: &mut Solution, _: &mut Context) {}
+impl<C: Cost> Explorer<C> for PassThroughExplorer {
+    fn explore(&mut self, _: &ATSP<C>, _: &mut Solution, _: &mut Context<C>) {}
 
-    fn stop_condition(&self, _: &Context) -> bool {
+    fn stop_condition(&self, _: &Context<C>) -> bool {
         true
     }
 }
@@ -29,44 +34,50 @@ impl RandomExplorer {
     }
 }
 
-impl Explorer for RandomExplorer {
-    fn explore(&mut self, instance: &ATSP, solution: &mut Solution, ctx: &mut Context) {
+impl<C: Cost> Explorer<C> for RandomExplorer {
+    fn explore(&mut self, instance: &ATSP<C>, solution: &mut Solution, ctx: &mut Context<C>) {
         utils::randomize_by_swaps(solution, &mut self.rng);
         let new_cost = instance.cost_of_solution(solution);
         ctx.evaluations += 1;
         ctx.current_cost = new_cost;
     }
 
-    fn stop_condition(&self, _: &Context) -> bool {
+    fn stop_condition(&self, _: &Context<C>) -> bool {
         false
     }
 }
 
 pub struct RandomWalkExplorer {
     rng: rand::rngs::StdRng,
-    op_flags: u32,
+    selector: OperatorSelector,
 }
 
 impl RandomWalkExplorer {
-    pub fn new(seed: u64, op_flags: u32) -> RandomWalkExplorer {
+    pub fn new(seed: u64, selector: OperatorSelector) -> RandomWalkExplorer {
         let rng = rand::SeedableRng::seed_from_u64(seed);
-        RandomWalkExplorer { rng, op_flags }
+        RandomWalkExplorer { rng, selector }
     }
 }
 
-impl Explorer for RandomWalkExplorer {
-    fn explore(&mut self, instance: &ATSP, solution: &mut Solution, ctx: &mut Context) {
-        let op =
-            operation::random_operation(&mut self.rng, instance.dimension as u16, self.op_flags);
+impl<C: Cost> Explorer<C> for RandomWalkExplorer {
+    fn explore(&mut self, instance: &ATSP<C>, solution: &mut Solution, ctx: &mut Context<C>) {
+        let (operator, op) = self
+            .selector
+            .sample(&mut self.rng, instance.dimension as u16);
         let cost_change = op.evaluate(solution, instance);
+        self.selector.feedback(operator, cost_change);
         ctx.current_cost += cost_change;
         op.apply(solution);
         ctx.evaluations += 1;
     }
 
-    fn stop_condition(&self, _: &Context) -> bool {
+    fn stop_condition(&self, _: &Context<C>) -> bool {
         false
     }
+
+    fn operator_stats(&self) -> Vec<OperatorStats> {
+        self.selector.stats().to_vec()
+    }
 }
 
 pub struct GreedySearchExplorer {
@@ -86,14 +97,15 @@ impl GreedySearchExplorer {
     }
 }
 
-impl Explorer for GreedySearchExplorer {
-    fn explore(&mut self, instance: &ATSP, solution: &mut Solution, ctx: &mut Context) {
+impl<C: Cost> Explorer<C> for GreedySearchExplorer {
+    fn explore(&mut self, instance: &ATSP<C>, solution: &mut Solution, ctx: &mut Context<C>) {
+        self.stop = false;
         utils::shuffle(&mut self.ops, &mut self.rng);
         for op in self.ops.iter() {
             let op_deserialized = operation::Operation::from_int(op.to_owned());
             let op_delta = op_deserialized.evaluate(solution, instance);
             ctx.evaluations += 1;
-            if op_delta < 0 {
+            if op_delta < C::ZERO {
                 op_deserialized.apply(solution);
                 ctx.current_cost += op_delta;
                 ctx.steps += 1;
@@ -103,92 +115,218 @@ impl Explorer for GreedySearchExplorer {
         self.stop = true;
     }
 
-    fn stop_condition(&self, _: &Context) -> bool {
+    fn stop_condition(&self, _: &Context<C>) -> bool {
         return self.stop;
     }
 }
 
-pub struct SteepestSearchExplorer {
+/// Worker threads evaluating a neighborhood, spawned by the first `explore` of a search
+/// with their own copy of the instance.
+struct Workers<C: Cost> {
+    pool: utils::WorkerPool,
+    instance: Arc<ATSP<C>>,
+}
+
+impl<C: Cost> Workers<C> {
+    /// Spawns the workers on the first call, returns `None` for a single thread.
+    fn get<'a>(
+        workers: &'a mut Option<Workers<C>>,
+        threads: usize,
+        instance: &ATSP<C>,
+    ) -> Option<&'a Workers<C>> {
+        if threads <= 1 {
+            return None;
+        }
+        Some(workers.get_or_insert_with(|| Workers {
+            pool: utils::WorkerPool::new(threads),
+            instance: Arc::new(instance.clone()),
+        }))
+    }
+}
+
+pub struct SteepestSearchExplorer<C: Cost> {
     rng: rand::rngs::StdRng,
     stop: bool,
     op_flags: u32,
+    threads: usize,
+    ops: Arc<Vec<u32>>,
+    workers: Option<Workers<C>>,
 }
 
-impl SteepestSearchExplorer {
-    pub fn new(seed: u64, op_flags: u32) -> SteepestSearchExplorer {
+impl<C: Cost> SteepestSearchExplorer<C> {
+    pub fn new(seed: u64, op_flags: u32, threads: usize) -> SteepestSearchExplorer<C> {
         let rng = rand::SeedableRng::seed_from_u64(seed);
         SteepestSearchExplorer {
             rng,
             stop: false,
             op_flags,
+            threads,
+            ops: Arc::new(Vec::new()),
+            workers: None,
         }
     }
 }
 
-impl Explorer for SteepestSearchExplorer {
-    fn explore(&mut self, instance: &ATSP, solution: &mut Solution, ctx: &mut Context) {
-        let mut best_ops: Vec<operation::Operation> = Vec::new();
-        let mut best_delta = std::i32::MAX;
-        for op in operation::NeighborhoodIterator::new(instance.dimension as u16, self.op_flags) {
-            let op_deserialized = operation::Operation::from_int(op);
-            let op_delta = op_deserialized.evaluate(solution, instance);
-            ctx.evaluations += 1;
-            if op_delta >= 0 || op_delta > best_delta {
+/// Best improving delta of a chunk of the neighborhood and all moves reaching it, in order.
+fn best_improving_moves<C: Cost>(
+    ops: &[u32],
+    instance: &ATSP<C>,
+    solution: &Solution,
+) -> (C, Vec<u32>) {
+    let mut best_ops: Vec<u32> = Vec::new();
+    let mut best_delta = C::MAX;
+    for &op in ops {
+        let op_delta = operation::Operation::from_int(op).evaluate(solution, instance);
+        if op_delta >= C::ZERO || op_delta > best_delta {
+            continue;
+        }
+        if op_delta < best_delta {
+            best_delta = op_delta;
+            best_ops.clear();
+        }
+        best_ops.push(op);
+    }
+    (best_delta, best_ops)
+}
+
+impl<C: Cost> Explorer<C> for SteepestSearchExplorer<C> {
+    fn explore(&mut self, instance: &ATSP<C>, solution: &mut Solution, ctx: &mut Context<C>) {
+        if self.ops.is_empty() {
+            self.ops = Arc::new(
+                operation::NeighborhoodIterator::new(instance.dimension as u16, self.op_flags)
+                    .collect(),
+            );
+        }
+        let chunks = Workers::get(&mut self.workers, self.threads, instance).and_then(|workers| {
+            let instance = Arc::clone(&workers.instance);
+            let solution = Arc::new(solution.clone());
+            workers
+                .pool
+                .map_chunks(&self.ops, move |_, chunk| {
+                    best_improving_moves(chunk, &instance, &solution)
+                })
+                .ok()
+        });
+        let chunks = chunks.unwrap_or_else(|| {
+            // Without workers, or after one panicked, the search goes on in this thread
+            self.workers = None;
+            self.threads = 1;
+            vec![best_improving_moves(&self.ops, instance, solution)]
+        });
+        ctx.evaluations += self.ops.len() as u32;
+
+        // Merging in chunk order keeps the candidates identical to a sequential scan
+        let mut best_ops: Vec<u32> = Vec::new();
+        let mut best_delta = C::MAX;
+        for (delta, ops) in chunks {
+            if ops.is_empty() || delta > best_delta {
                 continue;
             }
-            if op_delta < best_delta {
-                best_delta = op_delta;
+            if delta < best_delta {
+                best_delta = delta;
                 best_ops.clear();
             }
-            best_ops.push(op_deserialized);
+            best_ops.extend(ops);
         }
-        self.stop = best_delta >= 0;
+        self.stop = best_delta >= C::ZERO;
         if self.stop {
             return;
         }
         let sampled_idx = self.rng.gen_range(0..best_ops.len());
-        best_ops[sampled_idx].apply(solution);
+        operation::Operation::from_int(best_ops[sampled_idx]).apply(solution);
         ctx.current_cost += best_delta;
         ctx.steps += 1;
     }
 
-    fn stop_condition(&self, _: &Context) -> bool {
+    fn stop_condition(&self, _: &Context<C>) -> bool {
         return self.stop;
     }
 }
 
-pub struct TabuSearchExplorer {
+pub struct TabuSearchExplorer<C: Cost> {
     rng: rand::rngs::StdRng,
     op_flags: u32,
-    tabu_list: VecDeque<u32>,
+    memory: TabuMemory,
     patience: u32,
-    tabu_tenure: u32,
     elite_percentage: f64,
+    threads: usize,
+    workers: Option<Workers<C>>,
 }
 
-impl TabuSearchExplorer {
+/// Orders candidate moves by delta, ties broken by their position in the sampled subset.
+fn compare_candidates<C: Cost>(a: &(C, usize, u32), b: &(C, usize, u32)) -> Ordering {
+    a.0.partial_cmp(&b.0)
+        .unwrap_or(Ordering::Equal)
+        .then(a.1.cmp(&b.1))
+}
+
+/// Keeps the `size` best candidates, sorted with `compare_candidates`.
+fn keep_best_candidates<C: Cost>(candidates: &mut Vec<(C, usize, u32)>, size: usize) {
+    if size == 0 {
+        candidates.clear();
+        return;
+    }
+    if candidates.len() > size {
+        candidates.select_nth_unstable_by(size - 1, compare_candidates);
+        candidates.truncate(size);
+    }
+    candidates.sort_unstable_by(compare_candidates);
+}
+
+/// The `size` best moves of a chunk starting at `offset` in the sampled subset.
+fn elite_candidates<C: Cost>(
+    ops: &[u32],
+    offset: usize,
+    size: usize,
+    instance: &ATSP<C>,
+    solution: &Solution,
+) -> Vec<(C, usize, u32)> {
+    let mut candidates: Vec<(C, usize, u32)> = ops
+        .iter()
+        .enumerate()
+        .map(|(idx, &op)| {
+            let delta = operation::Operation::from_int(op).evaluate(solution, instance);
+            (delta, offset + idx, op)
+        })
+        .collect();
+    keep_best_candidates(&mut candidates, size);
+    candidates
+}
+
+impl<C: Cost> TabuSearchExplorer<C> {
     pub fn new(
         seed: u64,
         op_flags: u32,
         patience: u32,
         elite_percentage: f64,
-        tabu_tenure: u32,
-    ) -> TabuSearchExplorer {
+        memory: TabuMemory,
+        threads: usize,
+    ) -> TabuSearchExplorer<C> {
         TabuSearchExplorer {
             rng: rand::SeedableRng::seed_from_u64(seed),
             op_flags,
-            tabu_list: VecDeque::new(),
+            memory,
             patience,
             elite_percentage,
-            tabu_tenure,
+            threads,
+            workers: None,
         }
     }
 
+    pub fn memory(&self) -> &TabuMemory {
+        &self.memory
+    }
+
+    pub fn memory_mut(&mut self) -> &mut TabuMemory {
+        &mut self.memory
+    }
+
+    /// Best moves of a random subset of the neighborhood by delta, ties by subset position.
     fn build_top_moves(
         &mut self,
-        instance: &ATSP,
+        instance: &ATSP<C>,
         solution: &Solution,
-        ctx: &mut Context,
+        ctx: &mut Context<C>,
     ) -> Vec<operation::Operation> {
         let mut n_it =
             operation::NeighborhoodIterator::new(instance.dimension as u16, self.op_flags)
@@ -196,98 +334,104 @@ impl TabuSearchExplorer {
         utils::shuffle(&mut n_it, &mut self.rng);
 
         let subset_size = (self.elite_percentage * n_it.len() as f64).round();
-        let elite_size = (self.elite_percentage * subset_size).round();
-        let n_it_subset = &n_it[..subset_size as usize];
-        let mut top_operations_deltas: VecDeque<(i32, operation::Operation)> = VecDeque::new();
-
-        for op in n_it_subset {
-            let op_deserialized = operation::Operation::from_int(op.to_owned());
-            let delta = op_deserialized.evaluate(solution, instance);
-            ctx.evaluations += 1;
-
-            if (!top_operations_deltas.len() == elite_size as usize)
-                && delta >= top_operations_deltas.back().unwrap().0
-            {
-                continue;
-            }
-
-            let insert_index = top_operations_deltas
-                .binary_search_by(|&(d, _)| d.cmp(&delta))
-                .unwrap_or_else(|x| x);
-            top_operations_deltas.insert(
-                insert_index,
-                (
-                    delta,
-                    operation::Operation::from_int(op_deserialized.to_int()),
-                ),
-            );
-            if top_operations_deltas.len() > elite_size as usize {
-                top_operations_deltas.pop_back();
-            }
-        }
-        top_operations_deltas.into_iter().map(|x| x.1).collect()
-    }
-
-    fn update_tabu_list(&mut self, op: &operation::Operation) {
-        self.tabu_list.push_front(op.to_int());
-        if self.tabu_list.len() > self.tabu_tenure as usize {
-            self.tabu_list.pop_back();
-        }
+        let elite_size = (self.elite_percentage * subset_size).round() as usize;
+        n_it.truncate(subset_size as usize);
+        let n_it_subset = Arc::new(n_it);
+
+        let chunks = Workers::get(&mut self.workers, self.threads, instance).and_then(|workers| {
+            let instance = Arc::clone(&workers.instance);
+            let solution = Arc::new(solution.clone());
+            workers
+                .pool
+                .map_chunks(&n_it_subset, move |offset, chunk| {
+                    elite_candidates(chunk, offset, elite_size, &instance, &solution)
+                })
+                .ok()
+        });
+        let chunks = chunks.unwrap_or_else(|| {
+            // Without workers, or after one panicked, the search goes on in this thread
+            self.workers = None;
+            self.threads = 1;
+            vec![elite_candidates(
+                &n_it_subset,
+                0,
+                elite_size,
+                instance,
+                solution,
+            )]
+        });
+        ctx.evaluations += n_it_subset.len() as u32;
+
+        let mut top_operations_deltas: Vec<(C, usize, u32)> =
+            chunks.into_iter().flatten().collect();
+        keep_best_candidates(&mut top_operations_deltas, elite_size);
+        top_operations_deltas
+            .into_iter()
+            .map(|(_, _, op)| operation::Operation::from_int(op))
+            .collect()
     }
 }
-
-impl Explorer for TabuSearchExplorer {
-    fn explore(&mut self, instance: &ATSP, solution: &mut Solution, ctx: &mut Context) {
+impl<C: Cost> Explorer<C> for TabuSearchExplorer<C> {
+    fn explore(&mut self, instance: &ATSP<C>, solution: &mut Solution, ctx: &mut Context<C>) {
         let top_moves = self.build_top_moves(instance, solution, ctx);
-        let mut selected_op: Option<operation::Operation> = None;
-        let mut selected_delta: Option<i32> = None;
+        let mut selected: Option<(operation::Operation, C)> = None;
+        // Fallback when every move is tabu: the one whose tabu status expires first
+        let mut least_tabu: Option<(operation::Operation, u32)> = None;
         for op in top_moves {
             let delta = op.evaluate(solution, instance);
             ctx.evaluations += 1;
             let improves_best = delta + ctx.current_cost < ctx.best_cost;
-            if self.tabu_list.contains(&op.to_int()) && !improves_best {
+            if self.memory.is_tabu(&op, &solution.order) && !improves_best {
+                let expiry = self.memory.expiry(&op, &solution.order);
+                if least_tabu.as_ref().is_none_or(|(_, e)| expiry < *e) {
+                    least_tabu = Some((op, expiry));
+                }
                 continue;
             }
-            selected_op = Some(op);
-            selected_delta = Some(delta);
+            if improves_best {
+                self.memory.on_new_best();
+            }
+            selected = Some((op, delta));
             break;
         }
-        let concrete_op = match selected_op {
-            Some(op) => {
-                self.update_tabu_list(&op);
-                op
-            }
+        let (op, delta) = match selected {
+            Some(selected) => selected,
             None => {
-                let op = operation::Operation::from_int(self.tabu_list.back().unwrap().to_owned());
-                selected_delta = Some(op.evaluate(solution, instance));
-                op
+                self.memory.on_blocked();
+                let Some((op, _)) = least_tabu else {
+                    return;
+                };
+                let delta = op.evaluate(solution, instance);
+                (op, delta)
             }
         };
+        self.memory.record(&op, &solution.order, &mut self.rng);
         ctx.steps += 1;
-        ctx.current_cost += selected_delta.expect("Delta not found");
-        concrete_op.apply(solution);
+        ctx.current_cost += delta;
+        op.apply(solution);
     }
 
-    fn stop_condition(&self, ctx: &Context) -> bool {
+    fn stop_condition(&self, ctx: &Context<C>) -> bool {
         ctx.iterations_without_improvement >= self.patience
     }
 }
 
 pub struct SimulatedAnnealingExplorer {
     rng: rand::rngs::StdRng,
-    op_flags: u32,
+    selector: OperatorSelector,
     temperature: f64,
     alpha: f64,
     markov_chain_length: u32,
     tolerance_iterations: u32,
     no_improvement_counter: u32,
     cooldown_counter: u32,
+    accepted_in_chain: u32,
 }
 
 impl SimulatedAnnealingExplorer {
     pub fn new(
         seed: u64,
-        op_flags: u32,
+        selector: OperatorSelector,
         temperature: f64,
         alpha: f64,
         markov_chain_length: u32,
@@ -295,46 +439,428 @@ impl SimulatedAnnealingExplorer {
         let rng = rand::SeedableRng::seed_from_u64(seed);
         SimulatedAnnealingExplorer {
             rng,
-            op_flags,
+            selector,
             temperature,
             alpha,
             markov_chain_length,
             tolerance_iterations: 15,
             no_improvement_counter: 0,
             cooldown_counter: 0,
+            accepted_in_chain: 0,
         }
     }
 }
 
-impl Explorer for SimulatedAnnealingExplorer {
-    fn explore(&mut self, instance: &ATSP, solution: &mut Solution, ctx: &mut Context) {
-        let op =
-            operation::random_operation(&mut self.rng, instance.dimension as u16, self.op_flags);
+impl<C: Cost> Explorer<C> for SimulatedAnnealingExplorer {
+    fn explore(&mut self, instance: &ATSP<C>, solution: &mut Solution, ctx: &mut Context<C>) {
+        let (operator, op) = self
+            .selector
+            .sample(&mut self.rng, instance.dimension as u16);
         let cost_change = op.evaluate(solution, instance);
+        self.selector.feedback(operator, cost_change);
         ctx.evaluations += 1;
-        let accept_probability = if cost_change < 0 {
+        let accept_probability = if cost_change < C::ZERO {
             1.0
         } else {
-            (-(cost_change as f64) / self.temperature).exp()
+            (-cost_change.to_f64() / self.temperature).exp()
         };
         let accept = utils::generate_decision(accept_probability, &mut self.rng);
         self.no_improvement_counter += 1;
         if accept {
             self.no_improvement_counter = 0;
+            self.accepted_in_chain += 1;
             ctx.current_cost += cost_change;
             ctx.steps += 1;
             op.apply(solution);
         }
+        self.cooldown_counter += 1;
         if self.cooldown_counter >= self.markov_chain_length {
+            ctx.chains.push(ChainStats {
+                evaluation: ctx.evaluations,
+                temperature: self.temperature,
+                acceptance_rate: self.accepted_in_chain as f64 / self.cooldown_counter as f64,
+            });
             self.cooldown_counter = 0;
+            self.accepted_in_chain = 0;
             self.temperature *= self.alpha;
         }
     }
 
-    fn stop_condition(&self, _: &Context) -> bool {
+    fn stop_condition(&self, _: &Context<C>) -> bool {
         let length_condition =
             self.no_improvement_counter >= self.tolerance_iterations * self.markov_chain_length;
         let temperature_condition = self.temperature < 0.01;
         length_condition || temperature_condition
     }
+
+    fn operator_stats(&self) -> Vec<OperatorStats> {
+        self.selector.stats().to_vec()
+    }
+}
+
+/// Samples random moves and lets a pluggable criterion decide which ones to accept.
+pub struct AcceptanceExplorer<C: Cost> {
+    rng: rand::rngs::StdRng,
+    selector: OperatorSelector,
+    criterion: Box<dyn AcceptanceCriterion<C>>,
+    patience: u32,
+}
+
+impl<C: Cost> AcceptanceExplorer<C> {
+    pub fn new(
+        seed: u64,
+        selector: OperatorSelector,
+        criterion: Box<dyn AcceptanceCriterion<C>>,
+        patience: u32,
+    ) -> AcceptanceExplorer<C> {
+        AcceptanceExplorer {
+            rng: rand::SeedableRng::seed_from_u64(seed),
+            selector,
+            criterion,
+            patience,
+        }
+    }
+}
+
+impl<C: Cost> Explorer<C> for AcceptanceExplorer<C> {
+    fn explore(&mut self, instance: &ATSP<C>, solution: &mut Solution, ctx: &mut Context<C>) {
+        let (operator, op) = self
+            .selector
+            .sample(&mut self.rng, instance.dimension as u16);
+        let cost_change = op.evaluate(solution, instance);
+        self.selector.feedback(operator, cost_change);
+        ctx.evaluations += 1;
+        let candidate = ctx.current_cost + cost_change;
+        if self
+            .criterion
+            .accept(ctx.current_cost, candidate, ctx.best_cost)
+        {
+            op.apply(solution);
+            ctx.current_cost = candidate;
+            ctx.steps += 1;
+        }
+    }
+
+    fn stop_condition(&self, ctx: &Context<C>) -> bool {
+        ctx.iterations_without_improvement >= self.patience
+    }
+
+    fn operator_stats(&self) -> Vec<OperatorStats> {
+        self.selector.stats().to_vec()
+    }
+}
+
+struct Replica<C: Cost> {
+    id: usize,
+    solution: Solution,
+    cost: C,
+    best_cost: C,
+}
+
+/// Replica exchange annealing: one Metropolis chain per temperature of a geometric
+/// ladder, with configuration swaps proposed between neighboring temperatures.
+pub struct ParallelTemperingExplorer<C: Cost> {
+    rng: rand::rngs::StdRng,
+    selector: OperatorSelector,
+    temperatures: Vec<f64>,
+    sweep_length: u32,
+    patience: u32,
+    replicas: Vec<Replica<C>>,
+    best: Option<(Solution, C)>,
+    swap_attempts: Vec<u32>,
+    swap_accepts: Vec<u32>,
+}
+
+impl<C: Cost> ParallelTemperingExplorer<C> {
+    pub fn new(
+        seed: u64,
+        selector: OperatorSelector,
+        num_replicas: u32,
+        min_temperature: f64,
+        max_temperature: f64,
+        sweep_length: u32,
+        patience: u32,
+    ) -> ParallelTemperingExplorer<C> {
+        let num_replicas = num_replicas.max(1) as usize;
+        let ratio = if num_replicas > 1 {
+            (max_temperature / min_temperature).powf(1.0 / (num_replicas - 1) as f64)
+        } else {
+            1.0
+        };
+        ParallelTemperingExplorer {
+            rng: rand::SeedableRng::seed_from_u64(seed),
+            selector,
+            temperatures: (0..num_replicas)
+                .map(|i| min_temperature * ratio.powi(i as i32))
+                .collect(),
+            sweep_length,
+            patience,
+            replicas: Vec::new(),
+            best: None,
+            swap_attempts: vec![0; num_replicas.saturating_sub(1)],
+            swap_accepts: vec![0; num_replicas.saturating_sub(1)],
+        }
+    }
+
+    fn sweep(&mut self, slot: usize, instance: &ATSP<C>, ctx: &mut Context<C>) {
+        let temperature = self.temperatures[slot];
+        let replica = &mut self.replicas[slot];
+        for _ in 0..self.sweep_length {
+            let (operator, op) = self
+                .selector
+                .sample(&mut self.rng, instance.dimension as u16);
+            let cost_change = op.evaluate(&replica.solution, instance);
+            self.selector.feedback(operator, cost_change);
+            ctx.evaluations += 1;
+            let accept_probability = if cost_change < C::ZERO {
+                1.0
+            } else {
+                (-cost_change.to_f64() / temperature).exp()
+            };
+            if !utils::generate_decision(accept_probability, &mut self.rng) {
+                continue;
+            }
+            op.apply(&mut replica.solution);
+            replica.cost += cost_change;
+            ctx.steps += 1;
+            if replica.cost < replica.best_cost {
+                replica.best_cost = replica.cost;
+            }
+            if self
+                .best
+                .as_ref()
+                .is_none_or(|(_, cost)| replica.cost < *cost)
+            {
+                self.best = Some((replica.solution.clone(), replica.cost));
+            }
+        }
+    }
+
+    fn propose_swaps(&mut self) {
+        for slot in 0..self.replicas.len().saturating_sub(1) {
+            let (cold, hot) = (self.temperatures[slot], self.temperatures[slot + 1]);
+            let energy_diff =
+                self.replicas[slot].cost.to_f64() - self.replicas[slot + 1].cost.to_f64();
+            let accept_probability = ((1.0 / cold - 1.0 / hot) * energy_diff).exp();
+            self.swap_attempts[slot] += 1;
+            if utils::generate_decision(accept_probability, &mut self.rng) {
+                self.swap_accepts[slot] += 1;
+                self.replicas.swap(slot, slot + 1);
+            }
+        }
+    }
+}
+
+impl<C: Cost> Explorer<C> for ParallelTemperingExplorer<C> {
+    fn explore(&mut self, instance: &ATSP<C>, solution: &mut Solution, ctx: &mut Context<C>) {
+        if self.replicas.is_empty() {
+            self.replicas = (0..self.temperatures.len())
+                .map(|id| Replica {
+                    id,
+                    solution: solution.clone(),
+                    cost: ctx.current_cost,
+                    best_cost: ctx.current_cost,
+                })
+                .collect();
+            self.best = Some((solution.clone(), ctx.current_cost));
+        } else if ctx.current_cost < self.best.as_ref().unwrap().1 {
+            // A better solution given from outside, such as an island migrant, replaces the coldest replica
+            let replica = &mut self.replicas[0];
+            replica.solution = solution.clone();
+            replica.cost = ctx.current_cost;
+            if replica.cost < replica.best_cost {
+                replica.best_cost = replica.cost;
+            }
+            self.best = Some((solution.clone(), ctx.current_cost));
+        }
+        for slot in 0..self.replicas.len() {
+            self.sweep(slot, instance, ctx);
+        }
+        self.propose_swaps();
+
+        let (best_solution, best_cost) = self.best.as_ref().unwrap();
+        if *best_cost < ctx.best_cost {
+            *solution = best_solution.clone();
+            ctx.current_cost = *best_cost;
+        }
+    }
+
+    fn stop_condition(&self, ctx: &Context<C>) -> bool {
+        ctx.iterations_without_improvement >= self.patience
+    }
+
+    fn summary(&self) -> Option<String> {
+        let mut lines = vec!["Swap acceptance rates:".to_string()];
+        for (slot, (&attempts, &accepts)) in self
+            .swap_attempts
+            .iter()
+            .zip(&self.swap_accepts)
+            .enumerate()
+        {
+            let rate = if attempts > 0 {
+                accepts as f64 / attempts as f64
+            } else {
+                0.0
+            };
+            lines.push(format!(
+                "  T {:.3} <-> T {:.3}: {:.3} ({}/{})",
+                self.temperatures[slot],
+                self.temperatures[slot + 1],
+                rate,
+                accepts,
+                attempts
+            ));
+        }
+        lines.push("Replicas (final temperature, current cost, best cost):".to_string());
+        let mut replicas: Vec<(usize, &Replica<C>)> = self.replicas.iter().enumerate().collect();
+        replicas.sort_by_key(|(_, replica)| replica.id);
+        for (slot, replica) in replicas {
+            lines.push(format!(
+                "  #{}: T {:.3}, {}, {}",
+                replica.id, self.temperatures[slot], replica.cost, replica.best_cost
+            ));
+        }
+        Some(lines.join("\n"))
+    }
+
+    fn operator_stats(&self) -> Vec<OperatorStats> {
+        self.selector.stats().to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::initializers::{NearestNeighborInitializer, RandomInitializer};
+    use crate::search::{Initializer, SearchAlgorithm, SearchResult};
+    use crate::tabu::{TabuAttribute, Tenure};
+
+    fn run_with<E: Explorer<i32>>(explorer: &mut E) -> (Vec<u32>, i32, u32) {
+        let atsp: ATSP<i32> = ATSP::read_from_file("../data/ALL_atsp/ftv33.atsp").unwrap();
+        let mut initializer = RandomInitializer::new(5);
+        let SearchResult {
+            solution,
+            context: ctx,
+            ..
+        } = SearchAlgorithm::new(&atsp, &mut initializer, explorer, -1).run();
+        (solution.order, ctx.best_cost, ctx.evaluations)
+    }
+
+    #[test]
+    fn steepest_search_does_not_depend_on_threads() {
+        let sequential = run_with(&mut SteepestSearchExplorer::new(5, 0b11, 1));
+        let parallel = run_with(&mut SteepestSearchExplorer::new(5, 0b11, 4));
+        assert_eq!(sequential, parallel);
+    }
+
+    #[test]
+    fn tabu_search_does_not_depend_on_threads() {
+        let fixed_memory = || TabuMemory::new(TabuAttribute::Arcs, Tenure::Fixed(10));
+        let sequential = run_with(&mut TabuSearchExplorer::new(
+            5,
+            0b11,
+            20,
+            0.5,
+            fixed_memory(),
+            1,
+        ));
+        let parallel = run_with(&mut TabuSearchExplorer::new(
+            5,
+            0b11,
+            20,
+            0.5,
+            fixed_memory(),
+            3,
+        ));
+        assert_eq!(sequential, parallel);
+    }
+
+    #[test]
+    fn simulated_annealing_cools_after_every_chain() {
+        let mut explorer =
+            SimulatedAnnealingExplorer::new(5, OperatorSelector::uniform(0b11), 100.0, 0.5, 50);
+        let atsp: ATSP<i32> = ATSP::read_from_file("../data/ALL_atsp/ftv33.atsp").unwrap();
+        let mut initializer = RandomInitializer::new(5);
+        let SearchResult { context: ctx, .. } =
+            SearchAlgorithm::new(&atsp, &mut initializer, &mut explorer, -1)
+                .with_sample_interval(Some(100))
+                .run();
+        let chains = ctx.evaluations / 50;
+        assert!(chains > 0);
+        assert_eq!(explorer.temperature, 100.0 * 0.5f64.powi(chains as i32));
+        assert_eq!(ctx.chains.len() as u32, chains);
+        assert_eq!(ctx.chains[1].temperature, 50.0);
+        let uses: u32 = ctx.operator_stats.iter().map(|stats| stats.uses).sum();
+        assert_eq!(uses, ctx.evaluations);
+        assert!(ctx
+            .chains
+            .iter()
+            .all(|chain| (0.0..=1.0).contains(&chain.acceptance_rate)));
+        assert_eq!(ctx.samples.len() as u32, ctx.evaluations / 100);
+        assert_eq!(ctx.samples[0].evaluation, 100);
+        assert!(ctx
+            .samples
+            .iter()
+            .all(|sample| sample.best <= sample.current));
+    }
+
+    #[test]
+    fn parallel_tempering_temperature_ladder() {
+        let explorer: ParallelTemperingExplorer<i32> = ParallelTemperingExplorer::new(
+            0,
+            OperatorSelector::uniform(0b11),
+            3,
+            1.0,
+            100.0,
+            10,
+            5,
+        );
+        assert_eq!(explorer.temperatures.len(), 3);
+        assert!((explorer.temperatures[1] - 10.0).abs() < 1e-9);
+        assert!((explorer.temperatures[2] - 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn parallel_tempering_tracks_true_cost() {
+        let atsp: ATSP<i32> = ATSP::read_from_file("../data/ALL_atsp/ftv33.atsp").unwrap();
+        let mut explorer = ParallelTemperingExplorer::new(
+            1,
+            OperatorSelector::uniform(0b11),
+            4,
+            5.0,
+            200.0,
+            100,
+            20,
+        );
+        let mut initializer = RandomInitializer::new(1);
+        let SearchResult {
+            solution,
+            context: ctx,
+            ..
+        } = SearchAlgorithm::new(&atsp, &mut initializer, &mut explorer, -1).run();
+        assert_eq!(atsp.cost_of_solution(&solution), ctx.best_cost);
+        assert!(ctx.best_cost < ctx.initial_cost);
+        for replica in explorer.replicas.iter() {
+            assert_eq!(atsp.cost_of_solution(&replica.solution), replica.cost);
+        }
+    }
+
+    #[test]
+    fn parallel_tempering_adopts_better_solutions() {
+        let atsp: ATSP<i32> = ATSP::read_from_file("../data/ALL_atsp/ftv33.atsp").unwrap();
+        // Without sweeps the replicas only change through the given solution
+        let mut explorer =
+            ParallelTemperingExplorer::new(1, OperatorSelector::uniform(0b11), 3, 1.0, 50.0, 0, 1);
+        let mut solution = RandomInitializer::new(1).initialize(&atsp);
+        let mut ctx = Context::new(atsp.cost_of_solution(&solution));
+        explorer.explore(&atsp, &mut solution, &mut ctx);
+
+        let mut migrant = NearestNeighborInitializer::new(1).initialize(&atsp);
+        let migrant_cost = atsp.cost_of_solution(&migrant);
+        assert!(migrant_cost < ctx.current_cost);
+        ctx.current_cost = migrant_cost;
+        explorer.explore(&atsp, &mut migrant, &mut ctx);
+        assert_eq!(explorer.best.as_ref().unwrap().1, migrant_cost);
+        assert!(explorer.replicas.iter().any(|r| r.cost == migrant_cost));
+    }
 }
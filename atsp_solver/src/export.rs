@@ -1,69 +1,312 @@
-use std::fs;
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
 
+use serde::{Deserialize, Serialize};
+
+use crate::cost::Cost;
+use crate::multistart::RunStatistics;
+use crate::operator_selection::OperatorStats;
+use crate::search::{ChainStats, Context, Sample};
 use crate::solution::Solution;
 
-fn vec_to_string<T: ToString>(vector: &Vec<T>) -> String {
-    let mut result = "[".to_string();
-    for (index, element) in vector.iter().enumerate() {
-        result.push_str(element.to_string().as_str());
-        if index < vector.len() - 1 {
-            result.push_str(", ")
+/// Version of the result format, increased whenever a field is renamed, removed or changes meaning.
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// Columns of `RunResult::to_csv_row`.
+pub const CSV_HEADER: &str = "instance,method,init,neighborhood,seed,cost,initial_cost,time,\
+iterations,steps,evaluations,max_time_ns,max_evaluations,hyperparameters,timestamp";
+
+/// Result of a run. Any cost type reads back as `RunResult<f64>`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(bound = "C: Cost")]
+pub struct RunResult<C: Cost> {
+    pub schema_version: u32,
+    /// Version of the crate that produced the result
+    pub solver_version: String,
+    /// Seconds since the Unix epoch when the result was created
+    pub timestamp: u64,
+    pub instance: String,
+    pub method: String,
+    pub init: String,
+    pub neighborhood: String,
+    pub seed: u64,
+    /// Time limit in nanoseconds, non-positive for none
+    pub max_time_ns: i64,
+    pub max_evaluations: Option<u32>,
+    pub order: Vec<u32>,
+    pub cost: C,
+    pub initial_cost: C,
+    /// Mean running time in nanoseconds, -1 when the run was not timed
+    pub time: f64,
+    pub iterations: u32,
+    pub steps: u32,
+    pub evaluations: u32,
+    pub evaluations_history: Vec<u32>,
+    /// Empty in results written before the histories were recorded
+    #[serde(default)]
+    pub iterations_history: Vec<u32>,
+    /// Nanoseconds since the start of the search at every improvement
+    #[serde(default)]
+    pub time_history: Vec<f64>,
+    pub cost_history: Vec<C>,
+    pub hyperparameters: BTreeMap<String, f64>,
+    /// Resolved options of the run, which reproduce it when passed back with `--config`
+    pub config: serde_json::Value,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub operator_stats: Vec<OperatorStats>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub restarts: Option<RunStatistics>,
+    /// Costs sampled every `--sample-interval` evaluations
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub samples: Vec<Sample<C>>,
+    /// Markov chains of simulated annealing
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub chains: Vec<ChainStats>,
+}
+
+impl<C: Cost> RunResult<C> {
+    /// Result of the search, the fields describing the options are left empty.
+    pub fn new(instance: &str, solution: &Solution, context: &Context<C>) -> Self {
+        RunResult {
+            schema_version: SCHEMA_VERSION,
+            solver_version: env!("CARGO_PKG_VERSION").to_string(),
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |time| time.as_secs()),
+            instance: instance.to_string(),
+            method: String::new(),
+            init: String::new(),
+            neighborhood: String::new(),
+            seed: 0,
+            max_time_ns: -1,
+            max_evaluations: None,
+            order: solution.order.clone(),
+            cost: context.best_cost,
+            initial_cost: context.initial_cost,
+            time: -1.0,
+            iterations: context.iterations,
+            steps: context.steps,
+            evaluations: context.evaluations,
+            evaluations_history: context.evaluations_history.clone(),
+            iterations_history: context.iterations_history.clone(),
+            time_history: context.time_history.clone(),
+            cost_history: context.cost_history.clone(),
+            hyperparameters: BTreeMap::new(),
+            config: serde_json::Value::Null,
+            operator_stats: context.operator_stats.clone(),
+            restarts: None,
+            samples: context.samples.clone(),
+            chains: context.chains.clone(),
+        }
+    }
+
+    /// Fails on NaN or infinite costs, which JSON would only hold as `null`.
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        let costs = [self.cost, self.initial_cost]
+            .into_iter()
+            .chain(self.cost_history.iter().copied())
+            .chain(
+                self.samples
+                    .iter()
+                    .flat_map(|sample| [sample.best, sample.current]),
+            );
+        if let Some(cost) = costs
+            .map(|cost| cost.to_f64())
+            .find(|cost| !cost.is_finite())
+        {
+            return Err(serde::ser::Error::custom(format!(
+                "Cannot write the non-finite cost {} as JSON",
+                cost
+            )));
+        }
+        serde_json::to_string(self)
+    }
+
+    /// Fails with a clear error on results of another `SCHEMA_VERSION`.
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        let value: serde_json::Value = serde_json::from_str(json)?;
+        let version = value
+            .get("schema_version")
+            .and_then(serde_json::Value::as_u64);
+        if version != Some(SCHEMA_VERSION as u64) {
+            let found = version.map_or("no schema version".to_string(), |v| {
+                format!("schema version {}", v)
+            });
+            return Err(serde::de::Error::custom(format!(
+                "Result has {}, expected version {}",
+                found, SCHEMA_VERSION
+            )));
+        }
+        serde_json::from_value(value)
+    }
+
+    pub fn read_from_file(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(Self::from_json(&std::fs::read_to_string(path)?)?)
+    }
+
+    /// Summary of the run as a CSV row matching `CSV_HEADER`.
+    pub fn to_csv_row(&self) -> String {
+        let hyperparameters: Vec<String> = self
+            .hyperparameters
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect();
+        let max_evaluations = self
+            .max_evaluations
+            .map_or(String::new(), |max| max.to_string());
+        [
+            csv_field(&self.instance),
+            csv_field(&self.method),
+            csv_field(&self.init),
+            csv_field(&self.neighborhood),
+            self.seed.to_string(),
+            self.cost.to_string(),
+            self.initial_cost.to_string(),
+            self.time.to_string(),
+            self.iterations.to_string(),
+            self.steps.to_string(),
+            self.evaluations.to_string(),
+            self.max_time_ns.to_string(),
+            max_evaluations,
+            csv_field(&hyperparameters.join(";")),
+            self.timestamp.to_string(),
+        ]
+        .join(",")
+    }
+
+    /// Solution in the TSPLIB TOUR format, cities numbered from 1.
+    pub fn to_tour(&self) -> String {
+        let mut tour = format!(
+            "NAME : {}.{}.tour\nCOMMENT : Length {} found by {}\nTYPE : TOUR\nDIMENSION : {}\nTOUR_SECTION\n",
+            self.instance,
+            self.cost,
+            self.cost,
+            self.method,
+            self.order.len()
+        );
+        for city in self.order.iter() {
+            tour.push_str(&format!("{}\n", city + 1));
         }
+        tour.push_str("-1\nEOF\n");
+        tour
+    }
+
+    /// Long-format CSV with a row for the start, every improvement of the best cost
+    /// and every sample, in the order of the evaluations.
+    pub fn convergence_csv(&self) -> String {
+        // The current solution is the new best one whenever the best cost improves
+        let mut rows: Vec<Sample<C>> = (0..self.cost_history.len())
+            .map(|idx| Sample {
+                evaluation: self.evaluations_history[idx],
+                // Results written before these histories get iteration 0 and time NaN
+                iteration: self.iterations_history.get(idx).copied().unwrap_or(0),
+                time: self.time_history.get(idx).copied().unwrap_or(f64::NAN),
+                best: self.cost_history[idx],
+                current: self.cost_history[idx],
+            })
+            .chain(self.samples.iter().cloned())
+            .collect();
+        rows.sort_by_key(|row| row.evaluation);
+        let mut csv = "evaluation,iteration,time,best,current\n".to_string();
+        for row in rows {
+            csv.push_str(&format!(
+                "{},{},{},{},{}\n",
+                row.evaluation, row.iteration, row.time, row.best, row.current
+            ));
+        }
+        csv
     }
-    result.push(']');
-    result
 }
 
-pub fn export_to_file(
-    filename: &String,
-    solution: &Solution,
-    initial_cost: i32,
-    cost: i32,
-    time_per_run: f64,
-    iterations: u32,
-    steps: u32,
-    evaluations: u32,
-    method: &str,
-    instance: &str,
-    neighborhood: &str,
-    meta_param_1: f64,
-    meta_param_2: f64,
-    meta_param_3: f64,
-    evaluations_history: &Vec<u32>,
-    cost_history: &Vec<i32>,
-) {
-    let mut data: String = "{\n".to_string();
-    data.push_str("\t\"order\": ");
-    data.push_str(vec_to_string(&solution.order).as_str());
-    data.push_str(",\n\t\"cost\": ");
-    data.push_str(cost.to_string().as_str());
-    data.push_str(",\n\t\"initial_cost\": ");
-    data.push_str(initial_cost.to_string().as_str());
-    data.push_str(",\n\t\"time\": ");
-    data.push_str(time_per_run.to_string().as_str());
-    data.push_str(",\n\t\"iterations\": ");
-    data.push_str(iterations.to_string().as_str());
-    data.push_str(",\n\t\"steps\": ");
-    data.push_str(steps.to_string().as_str());
-    data.push_str(",\n\t\"evaluations\": ");
-    data.push_str(evaluations.to_string().as_str());
-    data.push_str(",\n\t\"method\": \"");
-    data.push_str(method);
-    data.push_str("\",\n\t\"instance\": \"");
-    data.push_str(instance);
-    data.push_str("\",\n\t\"neighborhood\": \"");
-    data.push_str(neighborhood);
-    data.push_str("\",\n\t\"evaluations_history\": ");
-    data.push_str(vec_to_string(evaluations_history).as_str());
-    data.push_str(",\n\t\"cost_history\": ");
-    data.push_str(vec_to_string(cost_history).as_str());
-    data.push_str(",\n\t\"meta-param-1\": ");
-    data.push_str(meta_param_1.to_string().as_str());
-    data.push_str(",\n\t\"meta-param-2\": ");
-    data.push_str(meta_param_2.to_string().as_str());
-    data.push_str(",\n\t\"meta-param-3\": ");
-    data.push_str(meta_param_3.to_string().as_str());
-    data.push_str("\n}");
-
-    fs::write(filename, data).expect("Failed to write to a file");
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn results_read_back_with_escaped_strings() {
+        let solution = Solution::new(&vec![2, 0, 1]).unwrap();
+        let mut context = Context::new(30);
+        context.best_cost = 21;
+        let mut result = RunResult::new("quoted \"name\"\\", &solution, &context);
+        result.method = "tabu-search".to_string();
+        result.hyperparameters.insert("tabu-elite".to_string(), 0.1);
+        let json = result.to_json().unwrap();
+        assert_eq!(RunResult::<i32>::from_json(&json).unwrap(), result);
+
+        let as_float = RunResult::<f64>::from_json(&json).unwrap();
+        assert_eq!((as_float.cost, as_float.initial_cost), (21.0, 30.0));
+        assert_eq!(as_float.instance, "quoted \"name\"\\");
+        assert_eq!(as_float.schema_version, SCHEMA_VERSION);
+        assert!(!json.contains("operator_stats") && !json.contains("restarts"));
+
+        let old = json.replace(
+            &format!("\"schema_version\":{}", SCHEMA_VERSION),
+            "\"schema_version\":1",
+        );
+        let error = RunResult::<i32>::from_json(&old).unwrap_err().to_string();
+        assert!(error.contains("schema version 1"), "{}", error);
+
+        let mut value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let fields = value.as_object_mut().unwrap();
+        fields.remove("iterations_history");
+        fields.remove("time_history");
+        let without_histories = RunResult::<i32>::from_json(&value.to_string()).unwrap();
+        assert!(without_histories.time_history.is_empty());
+        assert_eq!(without_histories.convergence_csv().lines().count(), 2);
+    }
+
+    #[test]
+    fn non_finite_costs_are_rejected() {
+        let solution = Solution::new(&vec![2, 0, 1]).unwrap();
+        let mut result = RunResult::new("x", &solution, &Context::new(3.0));
+        assert!(result.to_json().is_ok());
+        result.cost = f64::NAN;
+        assert!(result.to_json().is_err());
+        result.cost = 3.0;
+        result.cost_history.push(f64::INFINITY);
+        assert!(result.to_json().is_err());
+    }
+
+    #[test]
+    fn text_formats_describe_the_run() {
+        let solution = Solution::new(&vec![2, 0, 1]).unwrap();
+        let mut context = Context::new(30);
+        context.iterations = 4;
+        context.current_cost = 21;
+        context.on_change_best();
+        let mut result = RunResult::new("br3", &solution, &context);
+        result.method = "steepest-search".to_string();
+        result.hyperparameters.insert("a".to_string(), 0.5);
+        result.hyperparameters.insert("b".to_string(), 2.0);
+
+        let row = result.to_csv_row();
+        assert_eq!(row.split(',').count(), CSV_HEADER.split(',').count());
+        assert!(row.starts_with("br3,steepest-search,,,0,21,30,-1,4,0,0,-1,,a=0.5;b=2,"));
+        assert_eq!(
+            result.to_tour(),
+            "NAME : br3.21.tour\nCOMMENT : Length 21 found by steepest-search\nTYPE : TOUR\n\
+             DIMENSION : 3\nTOUR_SECTION\n3\n1\n2\n-1\nEOF\n"
+        );
+        result.samples.push(Sample {
+            evaluation: 50,
+            iteration: 9,
+            time: result.time_history[1] + 1.0,
+            best: 21,
+            current: 25,
+        });
+        let convergence = result.convergence_csv();
+        let convergence: Vec<&str> = convergence.lines().collect();
+        assert_eq!(convergence.len(), 4);
+        assert!(convergence[1].starts_with("0,0,"));
+        assert!(convergence[2].starts_with("0,4,") && convergence[2].ends_with(",21,21"));
+        assert!(convergence[3].starts_with("50,9,") && convergence[3].ends_with(",21,25"));
+    }
 }
@@ -0,0 +1,227 @@
+use rand::Rng;
+
+use crate::atsp::ATSP;
+use crate::cost::Cost;
+use crate::deltas::PenalizedCosts;
+use crate::matrix::{ArcCosts, Matrix};
+use crate::operation;
+use crate::search::{Context, Explorer};
+use crate::solution::Solution;
+use crate::utils;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Descent {
+    Steepest,
+    Greedy,
+}
+
+/// Guided local search: descends on costs augmented with arc penalties and,
+/// in every local optimum, penalizes the tour arcs of maximal utility
+/// `cost / (1 + penalty)`. The context always holds the true cost.
+pub struct GuidedLocalSearchExplorer<C: Cost> {
+    rng: rand::rngs::StdRng,
+    op_flags: u32,
+    descent: Descent,
+    alpha: f64,
+    patience: u32,
+    ops: Vec<u32>,
+    penalties: Vec<u32>,
+    penalty_costs: Vec<C>,
+    lambda: Option<C>,
+    local_optima: u32,
+}
+
+impl<C: Cost> GuidedLocalSearchExplorer<C> {
+    pub fn new(
+        seed: u64,
+        op_flags: u32,
+        descent: Descent,
+        alpha: f64,
+        patience: u32,
+    ) -> GuidedLocalSearchExplorer<C> {
+        GuidedLocalSearchExplorer {
+            rng: rand::SeedableRng::seed_from_u64(seed),
+            op_flags,
+            descent,
+            alpha,
+            patience,
+            ops: Vec::new(),
+            penalties: Vec::new(),
+            penalty_costs: Vec::new(),
+            lambda: None,
+            local_optima: 0,
+        }
+    }
+
+    /// Improving move on the augmented costs, if there is one.
+    fn find_move(
+        &mut self,
+        instance: &ATSP<C>,
+        solution: &Solution,
+        ctx: &mut Context<C>,
+    ) -> Option<operation::Operation> {
+        match &instance.matrix {
+            Matrix::Wide(matrix) => self.find_move_on(matrix, solution, ctx),
+            Matrix::Narrow(matrix) => self.find_move_on(matrix, solution, ctx),
+        }
+    }
+
+    fn find_move_on<M: ArcCosts<C>>(
+        &mut self,
+        matrix: &M,
+        solution: &Solution,
+        ctx: &mut Context<C>,
+    ) -> Option<operation::Operation> {
+        let costs = PenalizedCosts {
+            matrix,
+            dimension: solution.dimension,
+            penalties: &self.penalty_costs,
+        };
+        match self.descent {
+            Descent::Greedy => {
+                utils::shuffle(&mut self.ops, &mut self.rng);
+                for &op in self.ops.iter() {
+                    let op = operation::Operation::from_int(op);
+                    ctx.evaluations += 1;
+                    if op.evaluate_with(solution, &costs) < C::ZERO {
+                        return Some(op);
+                    }
+                }
+                None
+            }
+            Descent::Steepest => {
+                let mut best_ops: Vec<u32> = Vec::new();
+                let mut best_delta = C::ZERO;
+                for &op in self.ops.iter() {
+                    let delta = operation::Operation::from_int(op).evaluate_with(solution, &costs);
+                    if delta > best_delta || delta >= C::ZERO {
+                        continue;
+                    }
+                    if delta < best_delta {
+                        best_delta = delta;
+                        best_ops.clear();
+                    }
+                    best_ops.push(op);
+                }
+                ctx.evaluations += self.ops.len() as u32;
+                if best_ops.is_empty() {
+                    return None;
+                }
+                let sampled_idx = self.rng.gen_range(0..best_ops.len());
+                Some(operation::Operation::from_int(best_ops[sampled_idx]))
+            }
+        }
+    }
+
+    fn penalize(&mut self, instance: &ATSP<C>, solution: &Solution, ctx: &Context<C>) {
+        let n = instance.dimension;
+        let lambda = *self.lambda.get_or_insert_with(|| {
+            // Integer costs round small weights to zero, which would never change the descent
+            let lambda = C::from_f64(self.alpha * ctx.current_cost.to_f64() / n as f64);
+            if lambda > C::ZERO {
+                lambda
+            } else {
+                C::from_u16(1)
+            }
+        });
+        let arcs: Vec<usize> = (0..n)
+            .map(|i| solution.order[i] as usize * n + solution.order[(i + 1) % n] as usize)
+            .collect();
+        let utility = |arc: usize| {
+            instance.cost(arc / n, arc % n).to_f64() / (1.0 + self.penalties[arc] as f64)
+        };
+        let max_utility = arcs
+            .iter()
+            .map(|&arc| utility(arc))
+            .fold(f64::NEG_INFINITY, f64::max);
+        let penalized: Vec<usize> = arcs
+            .into_iter()
+            .filter(|&arc| utility(arc) >= max_utility)
+            .collect();
+        for arc in penalized {
+            self.penalties[arc] += 1;
+            self.penalty_costs[arc] += lambda;
+        }
+        self.local_optima += 1;
+    }
+}
+
+impl<C: Cost> Explorer<C> for GuidedLocalSearchExplorer<C> {
+    fn explore(&mut self, instance: &ATSP<C>, solution: &mut Solution, ctx: &mut Context<C>) {
+        if self.ops.is_empty() {
+            self.ops =
+                operation::NeighborhoodIterator::new(instance.dimension as u16, self.op_flags)
+                    .collect();
+            self.penalties = vec![0; instance.dimension * instance.dimension];
+            self.penalty_costs = vec![C::ZERO; instance.dimension * instance.dimension];
+        }
+        match self.find_move(instance, solution, ctx) {
+            Some(op) => {
+                ctx.current_cost += op.evaluate(solution, instance);
+                ctx.evaluations += 1;
+                op.apply(solution);
+                ctx.steps += 1;
+            }
+            None => self.penalize(instance, solution, ctx),
+        }
+    }
+
+    fn stop_condition(&self, ctx: &Context<C>) -> bool {
+        ctx.iterations_without_improvement >= self.patience
+    }
+
+    fn summary(&self) -> Option<String> {
+        let lambda = match self.lambda {
+            Some(lambda) => lambda.to_string(),
+            None => "-".to_string(),
+        };
+        Some(format!(
+            "Local optima: {}\nLambda: {}\nPenalized arcs: {}\nMax penalty: {}",
+            self.local_optima,
+            lambda,
+            self.penalties.iter().filter(|&&p| p > 0).count(),
+            self.penalties.iter().max().unwrap_or(&0)
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::explorers::SteepestSearchExplorer;
+    use crate::initializers::RandomInitializer;
+    use crate::search::{SearchAlgorithm, SearchResult};
+
+    #[test]
+    fn small_lambda_is_at_least_one_for_integer_costs() {
+        let atsp: ATSP<i32> = ATSP::read_from_file("../data/ALL_atsp/ftv33.atsp").unwrap();
+        let mut gls = GuidedLocalSearchExplorer::new(2, 0b11, Descent::Steepest, 0.001, 1);
+        let mut initializer = RandomInitializer::new(2);
+        SearchAlgorithm::new(&atsp, &mut initializer, &mut gls, -1).run();
+        assert_eq!(gls.lambda, Some(1));
+        assert!(gls.penalty_costs.iter().any(|&cost| cost > 0));
+    }
+
+    #[test]
+    fn gls_escapes_local_optima_and_reports_true_cost() {
+        let atsp: ATSP<i32> = ATSP::read_from_file("../data/ALL_atsp/ftv33.atsp").unwrap();
+
+        let mut initializer = RandomInitializer::new(2);
+        let mut steepest = SteepestSearchExplorer::new(2, 0b11, 1);
+        let ls_ctx = SearchAlgorithm::new(&atsp, &mut initializer, &mut steepest, -1)
+            .run()
+            .context;
+
+        let mut initializer = RandomInitializer::new(2);
+        let mut gls = GuidedLocalSearchExplorer::new(2, 0b11, Descent::Steepest, 0.3, 200);
+        let SearchResult {
+            solution,
+            context: ctx,
+            ..
+        } = SearchAlgorithm::new(&atsp, &mut initializer, &mut gls, -1).run();
+
+        assert_eq!(atsp.cost_of_solution(&solution), ctx.best_cost);
+        assert!(ctx.best_cost <= ls_ctx.best_cost);
+        assert!(gls.local_optima > 1);
+    }
+}
@@ -0,0 +1,250 @@
+use std::time::{Duration, Instant};
+
+use rand::Rng;
+
+use crate::atsp::ATSP;
+use crate::cost::Cost;
+use crate::initializers::NearestNeighborInitializer;
+use crate::operation::Operation;
+use crate::search::{Context, Explorer, Initializer};
+use crate::solution::Solution;
+
+/// Greedy randomized adaptive search: every iteration builds a tour with a randomized
+/// nearest neighbor, improves it with a descent and, optionally, relinks it
+/// towards a solution of the elite pool.
+pub struct GraspExplorer<C: Cost> {
+    rng: rand::rngs::StdRng,
+    constructor: NearestNeighborInitializer,
+    descent: Box<dyn Explorer<C>>,
+    patience: u32,
+    path_relinking: bool,
+    elite_size: usize,
+    elite: Vec<(Solution, C)>,
+    relinks: u32,
+    relink_improvements: u32,
+    max_time_ns: i64,
+    max_evaluations: Option<u32>,
+}
+
+impl<C: Cost> GraspExplorer<C> {
+    pub fn new(
+        seed: u64,
+        constructor: NearestNeighborInitializer,
+        descent: Box<dyn Explorer<C>>,
+        patience: u32,
+        path_relinking: bool,
+        elite_size: usize,
+    ) -> GraspExplorer<C> {
+        GraspExplorer {
+            rng: rand::SeedableRng::seed_from_u64(seed),
+            constructor,
+            descent,
+            patience,
+            path_relinking,
+            elite_size: elite_size.max(1),
+            elite: Vec::new(),
+            relinks: 0,
+            relink_improvements: 0,
+            max_time_ns: -1,
+            max_evaluations: None,
+        }
+    }
+
+    /// Limits of the search, checked during the descents so that a long descent cannot
+    /// run past them.
+    pub fn with_budget(mut self, max_time_ns: i64, max_evaluations: Option<u32>) -> Self {
+        self.max_time_ns = max_time_ns;
+        self.max_evaluations = max_evaluations;
+        self
+    }
+
+    /// Runs the descent to a local optimum, counting its work in the context.
+    fn descend(
+        &mut self,
+        instance: &ATSP<C>,
+        solution: &mut Solution,
+        cost: C,
+        ctx: &mut Context<C>,
+    ) -> C {
+        let deadline = (self.max_time_ns >= 0)
+            .then(|| ctx.started + Duration::from_nanos(self.max_time_ns as u64));
+        let mut descent_ctx = Context::new(cost);
+        loop {
+            self.descent.explore(instance, solution, &mut descent_ctx);
+            let out_of_time = deadline.is_some_and(|d| Instant::now() >= d);
+            let out_of_evaluations = self
+                .max_evaluations
+                .is_some_and(|max| ctx.evaluations.saturating_add(descent_ctx.evaluations) >= max);
+            if self.descent.stop_condition(&descent_ctx) || out_of_time || out_of_evaluations {
+                break;
+            }
+        }
+        ctx.evaluations += descent_ctx.evaluations;
+        ctx.steps += descent_ctx.steps;
+        descent_ctx.current_cost
+    }
+
+    /// Walks from `solution` to `guide` by swaps that place the guide's cities one position
+    /// at a time and returns the best intermediate tour strictly between the two.
+    fn relink(
+        &self,
+        instance: &ATSP<C>,
+        solution: &Solution,
+        cost: C,
+        guide: &Solution,
+        ctx: &mut Context<C>,
+    ) -> Option<(Solution, C)> {
+        let n = instance.dimension;
+        // Rotating the guide to start at the same city avoids relinking equal tours
+        let shift = guide
+            .order
+            .iter()
+            .position(|&c| c == solution.order[0])
+            .unwrap();
+        let target: Vec<u32> = (0..n).map(|k| guide.order[(shift + k) % n]).collect();
+
+        let mut current = solution.clone();
+        let mut current_cost = cost;
+        let mut positions = vec![0; n];
+        for (k, &city) in current.order.iter().enumerate() {
+            positions[city as usize] = k;
+        }
+        let mut best: Option<(Solution, C)> = None;
+        for k in 1..n {
+            if current.order[k] == target[k] {
+                continue;
+            }
+            let from = positions[target[k] as usize];
+            let op = Operation::node_swap(k as u16, from as u16);
+            current_cost += op.evaluate(&current, instance);
+            ctx.evaluations += 1;
+            op.apply(&mut current);
+            positions[current.order[k] as usize] = k;
+            positions[current.order[from] as usize] = from;
+            if current.order == target {
+                break;
+            }
+            if best.as_ref().is_none_or(|(_, c)| current_cost < *c) {
+                best = Some((current.clone(), current_cost));
+            }
+        }
+        best
+    }
+
+    fn update_elite(&mut self, solution: &Solution, cost: C) {
+        if self.elite.iter().any(|(s, _)| s.order == solution.order) {
+            return;
+        }
+        if self.elite.len() < self.elite_size {
+            self.elite.push((solution.clone(), cost));
+        } else if let Some(worst) = self
+            .elite
+            .iter_mut()
+            .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+            .filter(|worst| cost < worst.1)
+        {
+            *worst = (solution.clone(), cost);
+        }
+    }
+}
+
+impl<C: Cost> Explorer<C> for GraspExplorer<C> {
+    fn explore(&mut self, instance: &ATSP<C>, solution: &mut Solution, ctx: &mut Context<C>) {
+        let mut candidate = self.constructor.initialize(instance);
+        ctx.evaluations += Initializer::<C>::evaluations(&self.constructor);
+        let cost = instance.cost_of_solution(&candidate);
+        let mut cost = self.descend(instance, &mut candidate, cost, ctx);
+
+        if self.path_relinking && !self.elite.is_empty() {
+            let guide = self.elite[self.rng.gen_range(0..self.elite.len())]
+                .0
+                .clone();
+            self.relinks += 1;
+            if let Some((mut relinked, relinked_cost)) =
+                self.relink(instance, &candidate, cost, &guide, ctx)
+            {
+                let relinked_cost = self.descend(instance, &mut relinked, relinked_cost, ctx);
+                if relinked_cost < cost {
+                    self.relink_improvements += 1;
+                    self.update_elite(&candidate, cost);
+                    candidate = relinked;
+                    cost = relinked_cost;
+                }
+            }
+        }
+        self.update_elite(&candidate, cost);
+
+        ctx.steps += 1;
+        ctx.restarts += 1;
+        *solution = candidate;
+        ctx.current_cost = cost;
+    }
+
+    fn stop_condition(&self, ctx: &Context<C>) -> bool {
+        ctx.iterations_without_improvement >= self.patience
+    }
+
+    fn summary(&self) -> Option<String> {
+        Some(format!(
+            "Elite solutions: {}\nRelinks: {}\nImproving relinks: {}",
+            self.elite.len(),
+            self.relinks,
+            self.relink_improvements
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::explorers::{RandomWalkExplorer, SteepestSearchExplorer};
+    use crate::initializers::RandomInitializer;
+    use crate::operator_selection::OperatorSelector;
+    use crate::search::{SearchAlgorithm, SearchResult};
+
+    #[test]
+    fn grasp_with_path_relinking_reports_true_cost() {
+        let atsp: ATSP<i32> = ATSP::read_from_file("../data/ALL_atsp/ftv33.atsp").unwrap();
+        let mut explorer = GraspExplorer::new(
+            1,
+            NearestNeighborInitializer::new(1).with_alpha(0.3),
+            Box::new(SteepestSearchExplorer::new(1, 0b11, 1)),
+            10,
+            true,
+            4,
+        );
+        let mut initializer = RandomInitializer::new(1);
+        let SearchResult {
+            solution,
+            context: ctx,
+            ..
+        } = SearchAlgorithm::new(&atsp, &mut initializer, &mut explorer, -1).run();
+
+        assert_eq!(atsp.cost_of_solution(&solution), ctx.best_cost);
+        assert!(explorer.relinks > 0);
+        assert!(explorer.elite.len() <= 4);
+    }
+
+    #[test]
+    fn descent_stops_within_the_budget() {
+        let atsp: ATSP<i32> = ATSP::read_from_file("../data/ALL_atsp/ftv33.atsp").unwrap();
+        // A random walk never stops on its own
+        let descent = Box::new(RandomWalkExplorer::new(1, OperatorSelector::uniform(0b11)));
+        let mut explorer = GraspExplorer::new(
+            1,
+            NearestNeighborInitializer::new(1).with_alpha(0.3),
+            descent,
+            10,
+            false,
+            4,
+        )
+        .with_budget(-1, Some(5000));
+        let mut initializer = RandomInitializer::new(1);
+        let result = SearchAlgorithm::new(&atsp, &mut initializer, &mut explorer, -1)
+            .with_max_evaluations(Some(5000))
+            .run();
+
+        assert!(result.context.evaluations >= 5000);
+        assert!(result.context.evaluations < 6000);
+    }
+}
@@ -1,6 +1,7 @@
 use rand::Rng;
 
 use crate::atsp::ATSP;
+use crate::cost::Cost;
 use crate::search::Initializer;
 use crate::solution::Solution;
 use crate::utils;
@@ -16,8 +17,8 @@ impl RandomInitializer {
     }
 }
 
-impl Initializer for RandomInitializer {
-    fn initialize(&mut self, instance: &ATSP) -> Solution {
+impl<C: Cost> Initializer<C> for RandomInitializer {
+    fn initialize(&mut self, instance: &ATSP<C>) -> Solution {
         let cities: Vec<u32> = (0..(instance.dimension as u32)).collect();
         let mut initial_sol = Solution::new(&cities).unwrap();
         utils::randomize_by_swaps(&mut initial_sol, &mut self.rng);
@@ -25,34 +26,89 @@ impl Initializer for RandomInitializer {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Starts {
+    /// A single random start city
+    Random,
+    /// Every city, which makes greedy construction deterministic
+    All,
+    /// The given number of distinct random start cities
+    Sample(usize),
+}
+
+/// Nearest neighbor construction. With a positive `alpha` the next city is drawn
+/// from the restricted candidate list of unvisited cities costing at most
+/// `min + alpha * (max - min)`, so 0 is greedy and 1 is random.
+/// With several start cities the best of their tours is kept.
 pub struct NearestNeighborInitializer {
     rng: rand::rngs::StdRng,
+    alpha: f64,
+    starts: Starts,
+    evaluations: u32,
 }
 
 impl NearestNeighborInitializer {
     pub fn new(seed: u64) -> NearestNeighborInitializer {
         let rng = rand::SeedableRng::seed_from_u64(seed);
-        NearestNeighborInitializer { rng }
+        NearestNeighborInitializer {
+            rng,
+            alpha: 0.0,
+            starts: Starts::Random,
+            evaluations: 0,
+        }
+    }
+
+    pub fn with_alpha(mut self, alpha: f64) -> NearestNeighborInitializer {
+        self.alpha = alpha.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn with_starts(mut self, starts: Starts) -> NearestNeighborInitializer {
+        self.starts = starts;
+        self
+    }
+
+    fn next_city<C: Cost>(
+        &mut self,
+        instance: &ATSP<C>,
+        current: usize,
+        visited: &[bool],
+    ) -> usize {
+        let unvisited = visited.iter().enumerate().filter(|(_, &v)| !v);
+        if self.alpha <= 0.0 {
+            let mut next = 0;
+            let mut min_cost = C::MAX;
+            for (j, _) in unvisited {
+                if instance.cost(current, j) < min_cost {
+                    next = j;
+                    min_cost = instance.cost(current, j);
+                }
+            }
+            return next;
+        }
+        let costs: Vec<(usize, f64)> = unvisited
+            .map(|(j, _)| (j, instance.cost(current, j).to_f64()))
+            .collect();
+        let min = costs.iter().map(|c| c.1).fold(f64::INFINITY, f64::min);
+        let max = costs.iter().map(|c| c.1).fold(f64::NEG_INFINITY, f64::max);
+        let threshold = min + self.alpha * (max - min);
+        let candidates: Vec<usize> = costs
+            .into_iter()
+            .filter(|&(_, cost)| cost <= threshold)
+            .map(|(j, _)| j)
+            .collect();
+        candidates[self.rng.gen_range(0..candidates.len())]
     }
-}
 
-impl Initializer for NearestNeighborInitializer {
-    fn initialize(&mut self, instance: &ATSP) -> Solution {
+    fn construct<C: Cost>(&mut self, instance: &ATSP<C>, start: usize) -> Solution {
         let mut visited = vec![false; instance.dimension];
         let mut order = vec![0; instance.dimension];
 
-        let mut current = self.rng.gen_range(0..instance.dimension) as usize;
+        let mut current = start;
         visited[current] = true;
         order[0] = current as u32;
         for i in 1..instance.dimension {
-            let mut next = 0;
-            let mut min_cost = std::i32::MAX;
-            for j in 0..instance.dimension {
-                if !visited[j] && instance.matrix[current][j] < min_cost {
-                    next = j;
-                    min_cost = instance.matrix[current][j];
-                }
-            }
+            let next = self.next_city(instance, current, &visited);
             order[i] = next as u32;
             visited[next] = true;
             current = next;
@@ -60,3 +116,388 @@ impl Initializer for NearestNeighborInitializer {
         Solution::new(&order).unwrap()
     }
 }
+
+impl<C: Cost> Initializer<C> for NearestNeighborInitializer {
+    fn initialize(&mut self, instance: &ATSP<C>) -> Solution {
+        let n = instance.dimension;
+        let starts: Vec<usize> = match self.starts {
+            Starts::Random => {
+                self.evaluations = 0;
+                let start = self.rng.gen_range(0..n);
+                return self.construct(instance, start);
+            }
+            Starts::All => (0..n).collect(),
+            Starts::Sample(size) => {
+                let mut cities: Vec<usize> = (0..n).collect();
+                utils::shuffle(&mut cities, &mut self.rng);
+                cities.truncate(size.clamp(1, n));
+                cities
+            }
+        };
+        self.evaluations = starts.len() as u32;
+        starts
+            .into_iter()
+            .map(|start| {
+                let solution = self.construct(instance, start);
+                (instance.cost_of_solution(&solution), solution)
+            })
+            .reduce(|best, tour| if tour.0 < best.0 { tour } else { best })
+            .unwrap()
+            .1
+    }
+
+    fn evaluations(&self) -> u32 {
+        self.evaluations
+    }
+}
+
+/// Runs a stochastic initializer several times and keeps the best tour.
+pub struct RepeatedInitializer<C: Cost> {
+    inner: Box<dyn Initializer<C>>,
+    repeats: u32,
+    evaluations: u32,
+}
+
+impl<C: Cost> RepeatedInitializer<C> {
+    pub fn new(inner: Box<dyn Initializer<C>>, repeats: u32) -> RepeatedInitializer<C> {
+        RepeatedInitializer {
+            inner,
+            repeats: repeats.max(1),
+            evaluations: 0,
+        }
+    }
+}
+
+impl<C: Cost> Initializer<C> for RepeatedInitializer<C> {
+    fn initialize(&mut self, instance: &ATSP<C>) -> Solution {
+        self.evaluations = 0;
+        let mut best: Option<(C, Solution)> = None;
+        for _ in 0..self.repeats {
+            let solution = self.inner.initialize(instance);
+            let cost = instance.cost_of_solution(&solution);
+            self.evaluations += self.inner.evaluations() + 1;
+            if best.as_ref().is_none_or(|(best_cost, _)| cost < *best_cost) {
+                best = Some((cost, solution));
+            }
+        }
+        best.unwrap().1
+    }
+
+    fn evaluations(&self) -> u32 {
+        self.evaluations
+    }
+}
+
+/// Greedy arc matching: adds arcs from the cheapest one on, skipping arcs whose
+/// tail already has a successor, whose head already has a predecessor
+/// or which would close a cycle before all cities are joined.
+pub struct GreedyArcInitializer {}
+
+/// Root of the path fragment containing `city`, with path halving.
+fn fragment(fragments: &mut [usize], mut city: usize) -> usize {
+    while fragments[city] != city {
+        fragments[city] = fragments[fragments[city]];
+        city = fragments[city];
+    }
+    city
+}
+
+impl<C: Cost> Initializer<C> for GreedyArcInitializer {
+    fn initialize(&mut self, instance: &ATSP<C>) -> Solution {
+        let n = instance.dimension;
+        let mut arcs: Vec<(usize, usize)> = (0..n)
+            .flat_map(|from| {
+                (0..n)
+                    .filter(move |&to| to != from)
+                    .map(move |to| (from, to))
+            })
+            .collect();
+        arcs.sort_by(|a, b| {
+            instance
+                .cost(a.0, a.1)
+                .partial_cmp(&instance.cost(b.0, b.1))
+                .unwrap()
+                .then(a.cmp(b))
+        });
+
+        let mut successors: Vec<Option<usize>> = vec![None; n];
+        let mut has_predecessor = vec![false; n];
+        let mut fragments: Vec<usize> = (0..n).collect();
+        let mut joined = 0;
+        for (from, to) in arcs {
+            if joined == n - 1 {
+                break;
+            }
+            if successors[from].is_some() || has_predecessor[to] {
+                continue;
+            }
+            let (from_root, to_root) =
+                (fragment(&mut fragments, from), fragment(&mut fragments, to));
+            if from_root == to_root {
+                continue;
+            }
+            fragments[from_root] = to_root;
+            successors[from] = Some(to);
+            has_predecessor[to] = true;
+            joined += 1;
+        }
+
+        let mut current = has_predecessor.iter().position(|&p| !p).unwrap();
+        let mut order = Vec::with_capacity(n);
+        order.push(current as u32);
+        while let Some(next) = successors[current] {
+            order.push(next as u32);
+            current = next;
+        }
+        Solution::new(&order).unwrap()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InsertionRule {
+    /// The city with the cheapest insertion over all positions
+    Cheapest,
+    /// The city farthest from the partial tour
+    Farthest,
+    /// Cities in random order
+    Random,
+}
+
+/// Insertion heuristics: start from a random city and insert the city chosen by the
+/// rule at the position of the tour where it increases the cost the least.
+pub struct InsertionInitializer {
+    rng: rand::rngs::StdRng,
+    rule: InsertionRule,
+}
+
+impl InsertionInitializer {
+    pub fn new(seed: u64, rule: InsertionRule) -> InsertionInitializer {
+        let rng = rand::SeedableRng::seed_from_u64(seed);
+        InsertionInitializer { rng, rule }
+    }
+}
+
+/// Cheapest insertion of `city` into the tour stored as successors from `start`,
+/// returned as the city to insert it after and the cost increase.
+fn cheapest_insertion<C: Cost>(
+    instance: &ATSP<C>,
+    next: &[u32],
+    start: usize,
+    city: usize,
+) -> (usize, C) {
+    let mut best = (start, C::MAX);
+    let mut a = start;
+    loop {
+        let b = next[a] as usize;
+        let cost = insertion_cost(instance, a, city, b);
+        if cost < best.1 {
+            best = (a, cost);
+        }
+        a = b;
+        if a == start {
+            return best;
+        }
+    }
+}
+
+fn insertion_cost<C: Cost>(instance: &ATSP<C>, a: usize, city: usize, b: usize) -> C {
+    instance.cost(a, city) + instance.cost(city, b) - instance.cost(a, b)
+}
+
+impl<C: Cost> Initializer<C> for InsertionInitializer {
+    fn initialize(&mut self, instance: &ATSP<C>) -> Solution {
+        let n = instance.dimension;
+        let start = self.rng.gen_range(0..n);
+        // The partial tour as the successor of every city in it
+        let mut next = vec![start as u32; n];
+        let mut remaining: Vec<usize> = (0..n).filter(|&city| city != start).collect();
+        if self.rule == InsertionRule::Random {
+            utils::shuffle(&mut remaining, &mut self.rng);
+        }
+        // Distance of every city to the partial tour, in either direction
+        let mut distances: Vec<C> = (0..n)
+            .map(|city| {
+                let (to, from) = (instance.cost(start, city), instance.cost(city, start));
+                if to < from {
+                    to
+                } else {
+                    from
+                }
+            })
+            .collect();
+        // Cheapest insertion of every remaining city, kept up to date by the cheapest rule
+        let mut insertions: Vec<(usize, C)> = if self.rule == InsertionRule::Cheapest {
+            (0..n)
+                .map(|city| cheapest_insertion(instance, &next, start, city))
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        while !remaining.is_empty() {
+            let (idx, after) = match self.rule {
+                InsertionRule::Random => (
+                    0,
+                    cheapest_insertion(instance, &next, start, remaining[0]).0,
+                ),
+                InsertionRule::Cheapest => {
+                    let idx = (0..remaining.len())
+                        .min_by(|&a, &b| {
+                            insertions[remaining[a]]
+                                .1
+                                .partial_cmp(&insertions[remaining[b]].1)
+                                .unwrap()
+                        })
+                        .unwrap();
+                    (idx, insertions[remaining[idx]].0)
+                }
+                InsertionRule::Farthest => {
+                    let idx = (0..remaining.len())
+                        .max_by(|&a, &b| {
+                            distances[remaining[a]]
+                                .partial_cmp(&distances[remaining[b]])
+                                .unwrap()
+                                .then(b.cmp(&a))
+                        })
+                        .unwrap();
+                    (
+                        idx,
+                        cheapest_insertion(instance, &next, start, remaining[idx]).0,
+                    )
+                }
+            };
+            let city = remaining.remove(idx);
+            let before = next[after] as usize;
+            next[city] = before as u32;
+            next[after] = city as u32;
+            for &other in remaining.iter() {
+                for cost in [instance.cost(city, other), instance.cost(other, city)] {
+                    if cost < distances[other] {
+                        distances[other] = cost;
+                    }
+                }
+            }
+            if self.rule == InsertionRule::Cheapest {
+                // Only the arc (after, before) was removed, so the other cached positions
+                // stay valid and just compete with the two new arcs
+                for &other in remaining.iter() {
+                    if insertions[other].0 == after {
+                        insertions[other] = cheapest_insertion(instance, &next, start, other);
+                        continue;
+                    }
+                    for (a, b) in [(after, city), (city, before)] {
+                        let cost = insertion_cost(instance, a, other, b);
+                        if cost < insertions[other].1 {
+                            insertions[other] = (a, cost);
+                        }
+                    }
+                }
+            }
+        }
+        let mut tour = vec![start as u32];
+        while tour.len() < n {
+            tour.push(next[*tour.last().unwrap() as usize]);
+        }
+        Solution::new(&tour).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restricted_candidate_list_spans_greedy_to_random() {
+        let atsp: ATSP<i32> = ATSP::read_from_file("../data/ALL_atsp/ftv33.atsp").unwrap();
+        let cost = |alpha: f64, seed: u64| {
+            let mut initializer = NearestNeighborInitializer::new(seed).with_alpha(alpha);
+            atsp.cost_of_solution(&initializer.initialize(&atsp))
+        };
+        assert_eq!(cost(0.0, 4), {
+            let mut initializer = NearestNeighborInitializer::new(4);
+            atsp.cost_of_solution(&initializer.initialize(&atsp))
+        });
+        let greedy: i32 = (0..10).map(|seed| cost(0.0, seed)).sum();
+        let randomized: i32 = (0..10).map(|seed| cost(1.0, seed)).sum();
+        assert!(greedy < randomized);
+    }
+
+    #[test]
+    fn all_starts_nearest_neighbor_is_deterministic_and_best() {
+        let atsp: ATSP<i32> = ATSP::read_from_file("../data/ALL_atsp/ftv33.atsp").unwrap();
+        let mut all = NearestNeighborInitializer::new(0).with_starts(Starts::All);
+        let best = atsp.cost_of_solution(&all.initialize(&atsp));
+        assert_eq!(Initializer::<i32>::evaluations(&all), 34);
+        let mut other = NearestNeighborInitializer::new(7).with_starts(Starts::All);
+        assert_eq!(atsp.cost_of_solution(&other.initialize(&atsp)), best);
+        for seed in 0..10 {
+            let mut single = NearestNeighborInitializer::new(seed);
+            assert!(atsp.cost_of_solution(&single.initialize(&atsp)) >= best);
+        }
+    }
+
+    #[test]
+    fn repeated_initializer_counts_evaluations() {
+        use crate::explorers::PassThroughExplorer;
+        use crate::search::SearchAlgorithm;
+
+        let atsp: ATSP<i32> = ATSP::read_from_file("../data/ALL_atsp/ftv33.atsp").unwrap();
+        let nn = NearestNeighborInitializer::new(0).with_starts(Starts::Sample(3));
+        let mut initializer = RepeatedInitializer::new(Box::new(nn), 4);
+        let ctx = SearchAlgorithm::new(&atsp, &mut initializer, &mut PassThroughExplorer {}, -1)
+            .run()
+            .context;
+        assert_eq!(ctx.evaluations, 4 * (3 + 1));
+        assert_eq!(ctx.evaluations_history[0], 16);
+    }
+
+    #[test]
+    fn constructions_are_valid_and_beat_random_tours() {
+        let atsp: ATSP<i32> = ATSP::read_from_file("../data/ALL_atsp/ftv33.atsp").unwrap();
+        let random = atsp.cost_of_solution(&RandomInitializer::new(0).initialize(&atsp));
+        let mut initializers: Vec<Box<dyn Initializer<i32>>> = vec![
+            Box::new(GreedyArcInitializer {}),
+            Box::new(InsertionInitializer::new(0, InsertionRule::Cheapest)),
+            Box::new(InsertionInitializer::new(0, InsertionRule::Farthest)),
+            Box::new(InsertionInitializer::new(0, InsertionRule::Random)),
+        ];
+        for initializer in initializers.iter_mut() {
+            let solution = initializer.initialize(&atsp);
+            assert!(atsp.is_solution_valid(&solution).is_ok());
+            assert!(atsp.cost_of_solution(&solution) < random);
+        }
+    }
+
+    #[test]
+    fn cheapest_insertion_matches_a_full_rescan() {
+        // Instances without ties between the cheapest insertions, so both build the same tour
+        for name in ["ftv33", "ft53", "kro124p"] {
+            let path = format!("../data/ALL_atsp/{}.atsp", name);
+            let atsp: ATSP<i32> = ATSP::read_from_file(&path).unwrap();
+            let solution = InsertionInitializer::new(3, InsertionRule::Cheapest).initialize(&atsp);
+            // Rescans every position of every remaining city at each step
+            let mut tour = vec![solution.order[0]];
+            let mut remaining: Vec<u32> = (0..atsp.dimension as u32)
+                .filter(|&city| city != tour[0])
+                .collect();
+            while !remaining.is_empty() {
+                let mut best = (0, 0, i32::MAX);
+                for (idx, &city) in remaining.iter().enumerate() {
+                    for k in 0..tour.len() {
+                        let (a, b) = (tour[k] as usize, tour[(k + 1) % tour.len()] as usize);
+                        let cost = insertion_cost(&atsp, a, city as usize, b);
+                        if cost < best.2 {
+                            best = (idx, k + 1, cost);
+                        }
+                    }
+                }
+                tour.insert(best.1, remaining.remove(best.0));
+            }
+            let expected = Solution::new(&tour).unwrap();
+            assert_eq!(
+                atsp.cost_of_solution(&solution),
+                atsp.cost_of_solution(&expected)
+            );
+        }
+    }
+}
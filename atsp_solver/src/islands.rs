@@ -0,0 +1,305 @@
+use std::time::{Duration, Instant};
+
+use crate::atsp::ATSP;
+use crate::cost::Cost;
+use crate::operator_selection::{self, OperatorStats};
+use crate::search::{Context, Explorer, Initializer};
+use crate::solution::Solution;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Topology {
+    /// Island `i` sends its best solution to island `i + 1`
+    Ring,
+    /// Every island sends its best solution to all other islands
+    Full,
+}
+
+impl Topology {
+    pub fn targets(&self, island: usize, num_islands: usize) -> Vec<usize> {
+        if num_islands < 2 {
+            return Vec::new();
+        }
+        match self {
+            Topology::Ring => vec![(island + 1) % num_islands],
+            Topology::Full => (0..num_islands).filter(|&i| i != island).collect(),
+        }
+    }
+}
+
+/// Explorer of a single island and its initializer. An island without an initializer
+/// continues from the solution of the search.
+pub type IslandSetup<C> = (Box<dyn Explorer<C>>, Option<Box<dyn Initializer<C>>>);
+
+/// Limits of the search shared by all islands.
+struct Budget {
+    deadline: Option<Instant>,
+    /// Evaluations left to the island in the current epoch
+    evaluations: Option<u32>,
+}
+
+struct Island<C: Cost> {
+    explorer: Box<dyn Explorer<C>>,
+    initializer: Option<Box<dyn Initializer<C>>>,
+    solution: Option<Solution>,
+    best_solution: Option<Solution>,
+    ctx: Option<Context<C>>,
+    stopped: bool,
+    sources: Vec<usize>,
+}
+
+impl<C: Cost> Island<C> {
+    fn start(&mut self, instance: &ATSP<C>, solution: &Solution) {
+        let (solution, evaluations) = match self.initializer.as_mut() {
+            Some(initializer) => (initializer.initialize(instance), initializer.evaluations()),
+            None => (solution.clone(), 0),
+        };
+        let mut ctx = Context::new(instance.cost_of_solution(&solution));
+        ctx.on_initialized(evaluations);
+        self.ctx = Some(ctx);
+        self.best_solution = Some(solution.clone());
+        self.solution = Some(solution);
+    }
+
+    /// Runs up to `iterations` steps of the island's explorer within the budget.
+    fn run_epoch(&mut self, instance: &ATSP<C>, iterations: u32, budget: &Budget) {
+        let solution = self.solution.as_mut().unwrap();
+        let ctx = self.ctx.as_mut().unwrap();
+        let max_evaluations = budget
+            .evaluations
+            .map(|evaluations| ctx.evaluations.saturating_add(evaluations));
+        for _ in 0..iterations {
+            let out_of_time = budget.deadline.is_some_and(|d| Instant::now() >= d);
+            let out_of_evaluations = max_evaluations.is_some_and(|max| ctx.evaluations >= max);
+            if self.stopped || out_of_time || out_of_evaluations {
+                break;
+            }
+            self.explorer.explore(instance, solution, ctx);
+            if ctx.current_cost < ctx.best_cost {
+                self.best_solution = Some(solution.clone());
+                ctx.on_change_best();
+            }
+            self.stopped = self.explorer.stop_condition(ctx);
+            ctx.on_iteration_end();
+        }
+    }
+
+    /// Continues from the migrant if it is better than the current solution.
+    fn receive(&mut self, migrant: &Solution, cost: C) {
+        let ctx = self.ctx.as_mut().unwrap();
+        if cost < ctx.current_cost {
+            self.solution = Some(migrant.clone());
+            ctx.current_cost = cost;
+            if cost < ctx.best_cost {
+                self.best_solution = Some(migrant.clone());
+                ctx.on_change_best();
+            }
+        }
+    }
+
+    fn best_cost(&self) -> C {
+        self.ctx.as_ref().unwrap().best_cost
+    }
+}
+
+/// Runs several explorers in parallel threads that periodically exchange
+/// their best solutions. The shared context tracks the best island.
+pub struct IslandExplorer<C: Cost> {
+    islands: Vec<Island<C>>,
+    migration_interval: u32,
+    max_time_ns: i64,
+    max_evaluations: Option<u32>,
+    started: bool,
+}
+
+impl<C: Cost> IslandExplorer<C> {
+    pub fn new(
+        islands: Vec<IslandSetup<C>>,
+        topology: Topology,
+        migration_interval: u32,
+    ) -> IslandExplorer<C> {
+        let num_islands = islands.len();
+        let mut sources = vec![Vec::new(); num_islands];
+        for island in 0..num_islands {
+            for target in topology.targets(island, num_islands) {
+                sources[target].push(island);
+            }
+        }
+
+        let islands = islands
+            .into_iter()
+            .zip(sources)
+            .map(|((explorer, initializer), sources)| Island {
+                explorer,
+                initializer,
+                solution: None,
+                best_solution: None,
+                ctx: None,
+                stopped: false,
+                sources,
+            })
+            .collect();
+
+        IslandExplorer {
+            islands,
+            migration_interval,
+            max_time_ns: -1,
+            max_evaluations: None,
+            started: false,
+        }
+    }
+
+    /// Limits of the search, checked by the islands during an epoch.
+    /// The evaluations left are split evenly between the islands at the start of every epoch.
+    pub fn with_budget(mut self, max_time_ns: i64, max_evaluations: Option<u32>) -> Self {
+        self.max_time_ns = max_time_ns;
+        self.max_evaluations = max_evaluations;
+        self
+    }
+
+    /// Sends the best solution of every island to its targets. Each island keeps the best
+    /// migrant it receives, the first source winning ties.
+    fn migrate(&mut self) {
+        let bests: Vec<(Solution, C)> = self
+            .islands
+            .iter()
+            .map(|island| (island.best_solution.clone().unwrap(), island.best_cost()))
+            .collect();
+        for island in self.islands.iter_mut() {
+            let best_migrant = island
+                .sources
+                .iter()
+                .map(|&source| &bests[source])
+                .reduce(|best, migrant| if migrant.1 < best.1 { migrant } else { best });
+            if let Some((migrant, cost)) = best_migrant {
+                island.receive(migrant, *cost);
+            }
+        }
+    }
+}
+
+impl<C: Cost> Explorer<C> for IslandExplorer<C> {
+    fn explore(&mut self, instance: &ATSP<C>, solution: &mut Solution, ctx: &mut Context<C>) {
+        if !self.started {
+            for island in self.islands.iter_mut() {
+                island.start(instance, solution);
+            }
+            self.started = true;
+        }
+
+        let num_islands = self.islands.len() as u32;
+        let budget = Budget {
+            deadline: (self.max_time_ns >= 0)
+                .then(|| ctx.started + Duration::from_nanos(self.max_time_ns as u64)),
+            evaluations: self
+                .max_evaluations
+                .map(|max| max.saturating_sub(ctx.evaluations).div_ceil(num_islands)),
+        };
+        let iterations = self.migration_interval;
+        std::thread::scope(|scope| {
+            for island in self.islands.iter_mut() {
+                let budget = &budget;
+                scope.spawn(move || island.run_epoch(instance, iterations, budget));
+            }
+        });
+        self.migrate();
+
+        let island_contexts = self.islands.iter().map(|i| i.ctx.as_ref().unwrap());
+        ctx.evaluations = island_contexts.clone().map(|c| c.evaluations).sum();
+        ctx.steps = island_contexts.map(|c| c.steps).sum();
+        // NaN costs compare greater than any other cost
+        let best_island = self
+            .islands
+            .iter()
+            .min_by(|a, b| a.best_cost().to_f64().total_cmp(&b.best_cost().to_f64()))
+            .unwrap();
+        *solution = best_island.best_solution.clone().unwrap();
+        ctx.current_cost = best_island.best_cost();
+    }
+
+    fn stop_condition(&self, _: &Context<C>) -> bool {
+        self.islands.iter().all(|island| island.stopped)
+    }
+
+    fn operator_stats(&self) -> Vec<OperatorStats> {
+        let mut merged = Vec::new();
+        for island in self.islands.iter() {
+            operator_selection::merge_stats(&mut merged, &island.explorer.operator_stats());
+        }
+        merged
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::explorers::{ParallelTemperingExplorer, SteepestSearchExplorer};
+    use crate::initializers::RandomInitializer;
+    use crate::operator_selection::OperatorSelector;
+    use crate::search::{SearchAlgorithm, SearchResult};
+
+    #[test]
+    fn ring_and_full_targets() {
+        assert_eq!(Topology::Ring.targets(3, 4), vec![0]);
+        assert_eq!(Topology::Full.targets(1, 4), vec![0, 2, 3]);
+        assert!(Topology::Full.targets(0, 1).is_empty());
+    }
+
+    #[test]
+    fn islands_are_deterministic() {
+        let atsp: ATSP<i32> = ATSP::read_from_file("../data/ALL_atsp/ftv33.atsp").unwrap();
+        let run = || {
+            let islands: Vec<IslandSetup<i32>> = (0..3)
+                .map(|i| {
+                    let explorer: Box<dyn Explorer<i32>> =
+                        Box::new(SteepestSearchExplorer::new(i, 0b11, 1));
+                    let initializer: Box<dyn Initializer<i32>> =
+                        Box::new(RandomInitializer::new(i));
+                    (explorer, (i > 0).then_some(initializer))
+                })
+                .collect();
+            let mut explorer = IslandExplorer::new(islands, Topology::Full, 5);
+            let mut initializer = RandomInitializer::new(0);
+            let SearchResult {
+                solution,
+                context: ctx,
+                ..
+            } = SearchAlgorithm::new(&atsp, &mut initializer, &mut explorer, -1).run();
+            assert_eq!(atsp.cost_of_solution(&solution), ctx.best_cost);
+            (solution.order, ctx.best_cost, ctx.evaluations)
+        };
+        assert_eq!(run(), run());
+    }
+
+    #[test]
+    fn epochs_stop_within_the_budget() {
+        let atsp: ATSP<i32> = ATSP::read_from_file("../data/ALL_atsp/ftv33.atsp").unwrap();
+        let islands: Vec<IslandSetup<i32>> = (0..3)
+            .map(|i| {
+                let explorer: Box<dyn Explorer<i32>> = Box::new(ParallelTemperingExplorer::new(
+                    i,
+                    OperatorSelector::uniform(0b11),
+                    2,
+                    1.0,
+                    50.0,
+                    10,
+                    u32::MAX,
+                ));
+                let initializer: Box<dyn Initializer<i32>> = Box::new(RandomInitializer::new(i));
+                (explorer, (i > 0).then_some(initializer))
+            })
+            .collect();
+        // A single epoch would run forever without the budget
+        let mut explorer =
+            IslandExplorer::new(islands, Topology::Ring, u32::MAX).with_budget(-1, Some(3000));
+        let mut initializer = RandomInitializer::new(0);
+        let result = SearchAlgorithm::new(&atsp, &mut initializer, &mut explorer, -1)
+            .with_max_evaluations(Some(3000))
+            .run();
+        // Every island may finish its last sweeps of 2 x 10 evaluations
+        assert!((3000..3000 + 3 * 20).contains(&result.context.evaluations));
+        assert_eq!(
+            atsp.cost_of_solution(&result.solution),
+            result.context.best_cost
+        );
+    }
+}
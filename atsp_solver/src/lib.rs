@@ -0,0 +1,42 @@
+//! Metaheuristics for the asymmetric travelling salesman problem.
+//!
+//! An instance is read with [`ATSP::read_from_file`], and a search is configured
+//! with [`SearchBuilder`] from an [`Explorer`] and, optionally, an [`Initializer`].
+
+// Style of the original modules
+#![allow(
+    clippy::needless_return,
+    clippy::ptr_arg,
+    clippy::needless_range_loop,
+    clippy::manual_range_contains
+)]
+
+pub mod acceptance;
+pub mod alns;
+pub mod atsp;
+pub mod best_known;
+pub mod cost;
+pub mod deltas;
+pub mod errors;
+pub mod explorers;
+pub mod export;
+pub mod gls;
+pub mod grasp;
+pub mod initializers;
+pub mod islands;
+pub mod matrix;
+pub mod multistart;
+pub mod observers;
+pub mod operation;
+pub mod operator_selection;
+pub mod search;
+pub mod solution;
+pub mod stats;
+pub mod tabu;
+pub mod utils;
+
+pub use atsp::ATSP;
+pub use cost::Cost;
+pub use errors::MyError;
+pub use search::{Context, Explorer, Initializer, SearchAlgorithm, SearchBuilder, SearchResult};
+pub use solution::Solution;
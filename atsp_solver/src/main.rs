@@ -1,17 +1,16 @@
 mod args;
-mod atsp;
-mod deltas;
-mod errors;
-mod explorers;
-mod export;
-mod initializers;
-mod operation;
-mod search;
-mod solution;
-mod utils;
+mod bench;
+mod compare;
+mod tune;
+
+use std::io::Write;
 
 use args::alg_as_str;
-use clap::Parser;
+use evo_bio::{
+    acceptance, alns, atsp, explorers, export, gls, grasp, initializers, islands, multistart,
+    observers, operation, operator_selection, search, tabu, utils, Cost, SearchBuilder,
+    SearchResult,
+};
 
 fn op_flags_from_args(args: &args::Opt) -> u32 {
     let mut op_flags = operation::OperationFlags::NODE_SWAP | operation::OperationFlags::EDGE_SWAP;
@@ -24,85 +23,415 @@ fn op_flags_from_args(args: &args::Opt) -> u32 {
     op_flags.bits()
 }
 
-fn explorer_from_args(args: &args::Opt, instance: &atsp::ATSP) -> Box<dyn search::Explorer> {
-    let op_flags = op_flags_from_args(&args);
+fn operator_selector_from_args(args: &args::Opt) -> operator_selection::OperatorSelector {
+    let op_flags = op_flags_from_args(args);
+    let strategy = match args.operator_selection {
+        args::OperatorSelection::Uniform => {
+            return operator_selection::OperatorSelector::uniform(op_flags)
+        }
+        args::OperatorSelection::ProbabilityMatching => {
+            operator_selection::Strategy::ProbabilityMatching
+        }
+        args::OperatorSelection::AdaptivePursuit => operator_selection::Strategy::AdaptivePursuit,
+        args::OperatorSelection::Ucb => operator_selection::Strategy::Ucb,
+    };
+    operator_selection::OperatorSelector::new(
+        strategy,
+        op_flags,
+        args.os_min_probability,
+        args.os_adaptation_rate,
+        args.os_learning_rate,
+        args.os_exploration,
+    )
+}
+
+/// Reactive tabu search adapts the tenure itself, regardless of the tenure mode.
+fn tabu_memory_from_args(args: &args::Opt, num_nodes: u16, reactive: bool) -> tabu::TabuMemory {
+    let tenure = args.tabu_tenure * num_nodes as f64;
+    let min_tenure = (tenure * (1.0 - args.tabu_tenure_spread)).max(0.0) as u32;
+    let max_tenure = (tenure * (1.0 + args.tabu_tenure_spread)) as u32;
+    let tenure = match args.tabu_tenure_mode {
+        _ if reactive => tabu::Tenure::Adaptive(min_tenure, max_tenure),
+        args::TenureMode::Fixed => tabu::Tenure::Fixed(tenure as u32),
+        args::TenureMode::Random => tabu::Tenure::Random(min_tenure, max_tenure),
+        args::TenureMode::Reactive => tabu::Tenure::Reactive(min_tenure, max_tenure),
+    };
+    let attribute = match args.tabu_attribute {
+        args::TabuAttribute::Arcs => tabu::TabuAttribute::Arcs,
+        args::TabuAttribute::Cities => tabu::TabuAttribute::Cities,
+    };
+    tabu::TabuMemory::new(attribute, tenure)
+}
+
+fn explorer_from_args<C: Cost>(
+    args: &args::Opt,
+    instance: &atsp::ATSP<C>,
+    seed: u64,
+) -> Box<dyn search::Explorer<C>> {
+    build_explorer(&args.algorithm, args, instance, seed, 1.0)
+}
+
+/// `temperature_scale` multiplies the initial temperature of simulated annealing,
+/// which lets islands of the same algorithm explore at different temperatures.
+fn build_explorer<C: Cost>(
+    algorithm: &args::Algorithm,
+    args: &args::Opt,
+    instance: &atsp::ATSP<C>,
+    seed: u64,
+    temperature_scale: f64,
+) -> Box<dyn search::Explorer<C>> {
+    let op_flags = op_flags_from_args(args);
     let num_nodes = instance.dimension as u16;
-    match args.algorithm {
-        args::Algorithm::Random => Box::new(explorers::RandomExplorer::new(args.seed)),
-        args::Algorithm::RandomWalk => {
-            Box::new(explorers::RandomWalkExplorer::new(args.seed, op_flags))
-        }
-        args::Algorithm::GreedySearch | args::Algorithm::GreedySearchNN => Box::new(
-            explorers::GreedySearchExplorer::new(args.seed, num_nodes, op_flags),
-        ),
-        args::Algorithm::SteepestSearchNN | args::Algorithm::SteepestSearch => {
-            Box::new(explorers::SteepestSearchExplorer::new(args.seed, op_flags))
-        }
-        args::Algorithm::NNHeuristic => Box::new(explorers::PassThroughExplorer {}),
-        args::Algorithm::SimulatedAnnealing | args::Algorithm::SimulatedAnnealingNN => {
-            let markov_chain_length = (args.meta_param_3 * num_nodes as f64) as u32;
+    match algorithm {
+        args::Algorithm::Random => Box::new(explorers::RandomExplorer::new(seed)),
+        args::Algorithm::RandomWalk => Box::new(explorers::RandomWalkExplorer::new(
+            seed,
+            operator_selector_from_args(args),
+        )),
+        args::Algorithm::GreedySearch => Box::new(explorers::GreedySearchExplorer::new(
+            seed, num_nodes, op_flags,
+        )),
+        args::Algorithm::SteepestSearch => Box::new(explorers::SteepestSearchExplorer::new(
+            seed,
+            op_flags,
+            args.threads as usize,
+        )),
+        args::Algorithm::Construction => Box::new(explorers::PassThroughExplorer {}),
+        args::Algorithm::SimulatedAnnealing => {
+            let markov_chain_length = (args.sa_chain_length * num_nodes as f64) as u32;
             Box::new(explorers::SimulatedAnnealingExplorer::new(
-                args.seed,
-                op_flags,
-                args.meta_param_1,
-                args.meta_param_2,
+                seed,
+                operator_selector_from_args(args),
+                args.sa_t0 * temperature_scale,
+                args.sa_alpha,
                 markov_chain_length,
             ))
         }
-        args::Algorithm::TabuSearch | args::Algorithm::TabuSearchNN => {
-            let tenure = (args.meta_param_3 * num_nodes as f64) as u32;
-            Box::new(explorers::TabuSearchExplorer::new(
-                args.seed,
+        args::Algorithm::TabuSearch => Box::new(explorers::TabuSearchExplorer::new(
+            seed,
+            op_flags,
+            args.tabu_patience,
+            args.tabu_elite,
+            tabu_memory_from_args(args, num_nodes, false),
+            args.threads as usize,
+        )),
+        args::Algorithm::ReactiveTabuSearch => {
+            let tabu = explorers::TabuSearchExplorer::new(
+                seed,
                 op_flags,
-                args.meta_param_1 as u32,
-                args.meta_param_2,
-                tenure,
+                args.tabu_patience,
+                args.tabu_elite,
+                tabu_memory_from_args(args, num_nodes, true),
+                args.threads as usize,
+            );
+            Box::new(tabu::ReactiveTabuExplorer::new(
+                multistart::derive_seed(seed, 1),
+                tabu,
+                op_flags,
+                args.tabu_patience,
+                args.rts_max_escapes,
+                args.rts_diversification,
+                args.rts_elite,
+            ))
+        }
+        args::Algorithm::ThresholdAccepting
+        | args::Algorithm::GreatDeluge
+        | args::Algorithm::RecordToRecord
+        | args::Algorithm::LateAcceptance => {
+            let criterion: Box<dyn acceptance::AcceptanceCriterion<C>> = match algorithm {
+                args::Algorithm::ThresholdAccepting => Box::new(
+                    acceptance::ThresholdAccepting::new(args.ta_threshold, args.ta_decay),
+                ),
+                args::Algorithm::GreatDeluge => {
+                    Box::new(acceptance::GreatDeluge::new(args.gd_rain_speed))
+                }
+                args::Algorithm::RecordToRecord => {
+                    Box::new(acceptance::RecordToRecord::new(args.rrt_deviation))
+                }
+                _ => Box::new(acceptance::LateAcceptance::new(args.lahc_history)),
+            };
+            Box::new(explorers::AcceptanceExplorer::new(
+                seed,
+                operator_selector_from_args(args),
+                criterion,
+                args.acceptance_patience,
             ))
         }
+        args::Algorithm::GuidedLocalSearch => {
+            let descent = match args.gls_descent {
+                args::Descent::Steepest => gls::Descent::Steepest,
+                args::Descent::Greedy => gls::Descent::Greedy,
+            };
+            Box::new(gls::GuidedLocalSearchExplorer::new(
+                seed,
+                op_flags,
+                descent,
+                args.gls_alpha,
+                args.gls_patience,
+            ))
+        }
+        args::Algorithm::Alns => Box::new(alns::AlnsExplorer::new(
+            seed,
+            args.alns_destroy_min,
+            args.alns_destroy_max,
+            args.alns_decay,
+            args.alns_cooling,
+            args.alns_patience,
+        )),
+        args::Algorithm::Grasp => {
+            let descent: Box<dyn search::Explorer<C>> = match args.grasp_descent {
+                args::Descent::Steepest => Box::new(explorers::SteepestSearchExplorer::new(
+                    seed,
+                    op_flags,
+                    args.threads as usize,
+                )),
+                args::Descent::Greedy => Box::new(explorers::GreedySearchExplorer::new(
+                    seed, num_nodes, op_flags,
+                )),
+            };
+            Box::new(
+                grasp::GraspExplorer::new(
+                    seed,
+                    initializers::NearestNeighborInitializer::new(seed)
+                        .with_alpha(args.grasp_alpha),
+                    descent,
+                    args.grasp_patience,
+                    args.grasp_path_relinking,
+                    args.grasp_elite,
+                )
+                .with_budget(args.max_time_ns, args.max_evaluations),
+            )
+        }
+        args::Algorithm::ParallelTempering => {
+            let sweep_length = (args.pt_sweep * num_nodes as f64) as u32;
+            Box::new(explorers::ParallelTemperingExplorer::new(
+                seed,
+                operator_selector_from_args(args),
+                args.pt_replicas,
+                args.pt_min_temperature * temperature_scale,
+                args.pt_max_temperature * temperature_scale,
+                sweep_length,
+                args.pt_patience,
+            ))
+        }
+        args::Algorithm::IslandModel => {
+            let islands = (0..args.islands as usize)
+                .map(|i| {
+                    let island_seed = multistart::derive_seed(seed, i);
+                    let scale = args.island_temperature_spread.powi(i as i32);
+                    // The first island continues from the solution of the search
+                    (
+                        build_explorer(&args.island_algorithm, args, instance, island_seed, scale),
+                        (i > 0).then(|| initializer_from_args(args, island_seed)),
+                    )
+                })
+                .collect();
+            let topology = match args.topology {
+                args::Topology::Ring => islands::Topology::Ring,
+                args::Topology::Full => islands::Topology::Full,
+            };
+            Box::new(
+                islands::IslandExplorer::new(islands, topology, args.migration_interval)
+                    .with_budget(args.max_time_ns, args.max_evaluations),
+            )
+        }
     }
 }
 
-fn initializer_from_args(args: &args::Opt) -> Box<dyn search::Initializer> {
-    match args.algorithm {
-        args::Algorithm::NNHeuristic
-        | args::Algorithm::GreedySearchNN
-        | args::Algorithm::SteepestSearchNN
-        | args::Algorithm::SimulatedAnnealingNN
-        | args::Algorithm::TabuSearchNN => {
-            Box::new(initializers::NearestNeighborInitializer::new(args.seed))
+fn initializer_from_args<C: Cost>(args: &args::Opt, seed: u64) -> Box<dyn search::Initializer<C>> {
+    let initializer = build_initializer(args, seed);
+    if args.init_repeats > 1 {
+        return Box::new(initializers::RepeatedInitializer::new(
+            initializer,
+            args.init_repeats,
+        ));
+    }
+    initializer
+}
+
+fn build_initializer<C: Cost>(args: &args::Opt, seed: u64) -> Box<dyn search::Initializer<C>> {
+    match args.init {
+        args::Init::Random => Box::new(initializers::RandomInitializer::new(seed)),
+        args::Init::NearestNeighbor => {
+            let starts = match args.nn_starts {
+                0 => initializers::Starts::All,
+                1 => initializers::Starts::Random,
+                sample => initializers::Starts::Sample(sample),
+            };
+            Box::new(initializers::NearestNeighborInitializer::new(seed).with_starts(starts))
+        }
+        args::Init::GreedyArc => Box::new(initializers::GreedyArcInitializer {}),
+        args::Init::CheapestInsertion => Box::new(initializers::InsertionInitializer::new(
+            seed,
+            initializers::InsertionRule::Cheapest,
+        )),
+        args::Init::FarthestInsertion => Box::new(initializers::InsertionInitializer::new(
+            seed,
+            initializers::InsertionRule::Farthest,
+        )),
+        args::Init::RandomInsertion => Box::new(initializers::InsertionInitializer::new(
+            seed,
+            initializers::InsertionRule::Random,
+        )),
+    }
+}
+
+/// Early stopping applies to every run, while the progress and the trace
+/// are left out of the runs repeated to measure the time.
+fn observers_from_args<'a, C: Cost>(
+    args: &args::Opt,
+    seed: u64,
+    trace: Option<&'a std::fs::File>,
+    monitored: bool,
+) -> Vec<Box<dyn observers::Observer<C> + 'a>> {
+    let mut result: Vec<Box<dyn observers::Observer<C> + 'a>> = Vec::new();
+    if let Some(target) = args.target_cost {
+        result.push(Box::new(observers::TargetCost::new(C::from_f64(target))));
+    }
+    if let Some(max_evaluations) = args.stagnation {
+        result.push(Box::new(observers::StagnationLimit::new(max_evaluations)));
+    }
+    if monitored {
+        if args.progress {
+            let label = if args.restarts > 1 {
+                format!("[seed {}] ", seed)
+            } else {
+                String::new()
+            };
+            result.push(Box::new(observers::ProgressPrinter::new(&label, 1e9)));
+        }
+        if let Some(file) = trace {
+            result.push(Box::new(observers::TraceWriter::new(file, seed)));
         }
-        _ => Box::new(initializers::RandomInitializer::new(args.seed)),
     }
+    result
 }
 
-fn solution_from_args(
+fn solution_from_args<'a, C: Cost>(
     args: &args::Opt,
-    instance: &atsp::ATSP,
-) -> (solution::Solution, search::Context) {
-    let mut explorer: Box<dyn search::Explorer> = explorer_from_args(&args, &instance);
-    let mut initializer: Box<dyn search::Initializer> = initializer_from_args(&args);
-    let mut search_alg =
-        search::SearchAlgorithm::new(instance, &mut initializer, &mut explorer, args.max_time_ns);
-    search_alg.run()
+    instance: &'a atsp::ATSP<C>,
+    seed: u64,
+    observers: Vec<Box<dyn observers::Observer<C> + 'a>>,
+) -> SearchResult<C> {
+    let mut builder = SearchBuilder::new(instance, explorer_from_args(args, instance, seed))
+        .initializer(initializer_from_args(args, seed))
+        .seed(seed)
+        .max_time_ns(args.max_time_ns)
+        .max_evaluations(args.max_evaluations)
+        .sample_interval(args.sample_interval);
+    for observer in observers {
+        builder = builder.observer(observer);
+    }
+    builder.run()
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let args = args::Opt::parse();
-    let atsp = atsp::ATSP::read_from_file(&args.instance)?;
+    let args = args::Opt::resolve(std::env::args_os().collect()).unwrap_or_else(|e| e.exit());
+    match &args.command {
+        Some(args::Command::Bench(bench_args)) => return bench::run(bench_args),
+        Some(args::Command::Tune(tune_args)) => return tune::run(tune_args),
+        Some(args::Command::Compare(compare_args)) => return compare::run(compare_args),
+        None => {}
+    }
+    run_solver(&args)?;
+    Ok(())
+}
+
+/// Writes the result next to `output` with the extension of the format.
+/// JSON Lines and CSV are appended so that a file can collect many runs.
+fn write_output<C: Cost>(
+    result: &export::RunResult<C>,
+    format: &args::Format,
+    output: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let path = std::path::Path::new(output).with_extension(format.extension());
+    let append = |line: String, header: Option<&str>| -> std::io::Result<()> {
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)?;
+        if let Some(header) = header.filter(|_| file.metadata().is_ok_and(|m| m.len() == 0)) {
+            writeln!(file, "{}", header)?;
+        }
+        writeln!(file, "{}", line)
+    };
+    match format {
+        args::Format::Json => std::fs::write(&path, result.to_json()?)?,
+        args::Format::Jsonl => append(result.to_json()?, None)?,
+        args::Format::Csv => append(result.to_csv_row(), Some(export::CSV_HEADER))?,
+        args::Format::Tour => std::fs::write(&path, result.to_tour())?,
+    }
+    Ok(())
+}
 
-    assert!(atsp.dimension == atsp.matrix.len() && atsp.dimension > 0);
+/// Solves the instance described by the options, writes the requested outputs
+/// and returns the result as JSON.
+fn run_solver(args: &args::Opt) -> Result<String, Box<dyn std::error::Error>> {
+    match args.cost_type {
+        args::CostType::I32 => write_outputs(&solve::<i32>(args)?, args),
+        args::CostType::I64 => write_outputs(&solve::<i64>(args)?, args),
+        args::CostType::F64 => write_outputs(&solve::<f64>(args)?, args),
+    }
+}
+
+fn write_outputs<C: Cost>(
+    result: &export::RunResult<C>,
+    args: &args::Opt,
+) -> Result<String, Box<dyn std::error::Error>> {
+    if !args.output.is_empty() {
+        for format in args.format.iter() {
+            write_output(result, format, &args.output)?;
+        }
+    }
+    if !args.convergence.is_empty() {
+        std::fs::write(&args.convergence, result.convergence_csv())?;
+    }
+    Ok(result.to_json()?)
+}
+
+fn solve<C: Cost>(args: &args::Opt) -> Result<export::RunResult<C>, Box<dyn std::error::Error>> {
+    if let args::Algorithm::IslandModel = args.island_algorithm {
+        return Err("Island model cannot be used as the algorithm of an island".into());
+    }
+    let mut atsp: atsp::ATSP<C> = atsp::ATSP::read_from_file(&args.instance)?;
+
+    if args.narrow_matrix && !atsp.narrow_matrix() && args.verbose {
+        println!("Matrix values do not fit in u16, keeping wide storage");
+    }
 
     if args.verbose {
-        atsp.display(false);
+        println!("{}", atsp.describe(false));
 
         println!("\n========= CONFIG ==========");
         println!("{:#?}", args);
     }
 
-    let (solution, ctx) = solution_from_args(&args, &atsp);
-
-    assert_eq!(solution.order.len(), atsp.dimension as usize);
-    assert_eq!(ctx.best_cost, atsp.cost_of_solution(&solution));
+    // Restarts on several jobs share the trace, every row is appended at once
+    let trace = if args.trace.is_empty() {
+        None
+    } else {
+        std::fs::write(&args.trace, format!("{}\n", observers::TRACE_HEADER))?;
+        Some(std::fs::OpenOptions::new().append(true).open(&args.trace)?)
+    };
+    let run = |seed| {
+        let observers = observers_from_args(args, seed, trace.as_ref(), true);
+        solution_from_args(args, &atsp, seed, observers)
+    };
+    let (result, restart_stats) = if args.restarts > 1 {
+        let mut multistart =
+            multistart::run_multistart(args.restarts as usize, args.jobs as usize, args.seed, run);
+        let mut best = multistart.runs.swap_remove(multistart.best_run).result;
+        // Summaries describe a single run and are not comparable across restarts
+        best.summary = None;
+        (best, Some(multistart.stats))
+    } else {
+        (run(args.seed), None)
+    };
+    let SearchResult {
+        solution,
+        context: ctx,
+        summary: explorer_summary,
+    } = result;
 
     atsp.is_solution_valid(&solution)?;
     if args.verbose {
@@ -110,15 +439,42 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         println!("{:#?}", ctx);
 
         let it =
-            operation::NeighborhoodIterator::new(atsp.dimension as u16, op_flags_from_args(&args));
+            operation::NeighborhoodIterator::new(atsp.dimension as u16, op_flags_from_args(args));
         println!("Neighborhood Size: {}", it.size());
+
+        if let Some(summary) = &explorer_summary {
+            println!("\n========= EXPLORER ==========");
+            println!("{}", summary);
+        }
+
+        if let Some(stats) = &restart_stats {
+            println!("\n========= RESTARTS ==========");
+            println!("Runs: {} on {} jobs", stats.runs, stats.jobs);
+            println!("Best seed: {}", stats.best_seed);
+            println!(
+                "Cost: min {} | mean {:.2} | stdev {:.2} | median {}",
+                stats.cost.min, stats.cost.mean, stats.cost.stdev, stats.cost.median
+            );
+            println!(
+                "Time: mean {} | stdev {} | median {} | wall {}",
+                utils::humanize_time(stats.time_ns.mean),
+                utils::humanize_time(stats.time_ns.stdev),
+                utils::humanize_time(stats.time_ns.median),
+                utils::humanize_time(stats.wall_time_ns)
+            );
+        }
     }
 
     let mut avg_running_time: f64 = -1.0;
     if args.time {
-        avg_running_time = utils::measure_execution_time(|| {
-            solution_from_args(&args, &atsp);
-        });
+        // Restarts already time every run, so there is no need to repeat them
+        avg_running_time = match &restart_stats {
+            Some(stats) => stats.time_ns.mean,
+            None => utils::measure_execution_time(|| {
+                let observers = observers_from_args(args, args.seed, None, false);
+                solution_from_args(args, &atsp, args.seed, observers);
+            }),
+        };
         if args.verbose {
             println!("Time taken: {}", utils::humanize_time(avg_running_time));
         };
@@ -130,27 +486,22 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         neigborhood_type = "edge";
     }
 
-    if args.output == "" {
-        return Ok(());
-    }
-    export::export_to_file(
-        &args.output,
-        &solution,
-        ctx.initial_cost,
-        ctx.best_cost,
-        avg_running_time,
-        ctx.iterations,
-        ctx.steps,
-        ctx.evaluations,
-        alg_as_str(&args.algorithm),
-        atsp.name.as_str(),
-        neigborhood_type,
-        args.meta_param_1,
-        args.meta_param_2,
-        args.meta_param_3,
-        &ctx.evaluations_history,
-        &ctx.cost_history,
-    );
-
-    Ok(())
+    let result = export::RunResult {
+        method: alg_as_str(&args.algorithm).to_string(),
+        init: args::init_as_str(&args.init).to_string(),
+        neighborhood: neigborhood_type.to_string(),
+        seed: args.seed,
+        max_time_ns: args.max_time_ns,
+        max_evaluations: args.max_evaluations,
+        time: avg_running_time,
+        hyperparameters: args
+            .hyperparameters(&args.algorithm)
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect(),
+        config: serde_json::to_value(args)?,
+        restarts: restart_stats,
+        ..export::RunResult::new(&atsp.name, &solution, &ctx)
+    };
+    Ok(result)
 }
@@ -0,0 +1,170 @@
+use std::marker::PhantomData;
+
+use crate::cost::Cost;
+use crate::errors::MyError;
+
+/// Source of arc costs for the delta functions.
+pub trait ArcCosts<C: Cost> {
+    fn arc(&self, from: usize, to: usize) -> C;
+}
+
+/// Type of the stored matrix values, converted to the cost type on every lookup.
+pub trait Element<C: Cost>: Copy + Send + Sync {
+    fn from_cost(cost: C) -> Option<Self>;
+
+    fn to_cost(self) -> C;
+}
+
+impl<C: Cost> Element<C> for C {
+    #[inline(always)]
+    fn from_cost(cost: C) -> Option<Self> {
+        Some(cost)
+    }
+
+    #[inline(always)]
+    fn to_cost(self) -> C {
+        self
+    }
+}
+
+/// Halves the memory traffic of the delta lookups, only available when every value fits.
+impl<C: Cost> Element<C> for u16 {
+    fn from_cost(cost: C) -> Option<Self> {
+        cost.to_u16()
+    }
+
+    #[inline(always)]
+    fn to_cost(self) -> C {
+        C::from_u16(self)
+    }
+}
+
+/// Row-major cost matrix storing its values as `E`.
+#[derive(Clone)]
+pub struct CostMatrix<C: Cost, E: Element<C> = C> {
+    dimension: usize,
+    values: Vec<E>,
+    cost: PhantomData<C>,
+}
+
+impl<C: Cost> CostMatrix<C> {
+    pub fn new(dimension: usize, values: Vec<C>) -> Result<Self, MyError> {
+        if values.len() != dimension * dimension {
+            return Err(MyError::MatrixSizeMismatch);
+        }
+        Ok(CostMatrix {
+            dimension,
+            values,
+            cost: PhantomData,
+        })
+    }
+
+    /// Returns a copy backed by `u16` storage, or `None` if some value does not fit.
+    pub fn narrowed(&self) -> Option<CostMatrix<C, u16>> {
+        Some(CostMatrix {
+            dimension: self.dimension,
+            values: self
+                .values
+                .iter()
+                .map(|&v| u16::from_cost(v))
+                .collect::<Option<Vec<u16>>>()?,
+            cost: PhantomData,
+        })
+    }
+}
+
+impl<C: Cost, E: Element<C>> CostMatrix<C, E> {
+    pub fn dimension(&self) -> usize {
+        self.dimension
+    }
+
+    #[inline(always)]
+    pub fn get(&self, from: usize, to: usize) -> C {
+        self.values[from * self.dimension + to].to_cost()
+    }
+
+    pub fn row(&self, from: usize) -> impl Iterator<Item = C> + '_ {
+        (0..self.dimension).map(move |to| self.get(from, to))
+    }
+}
+
+impl<C: Cost, E: Element<C>> ArcCosts<C> for CostMatrix<C, E> {
+    #[inline(always)]
+    fn arc(&self, from: usize, to: usize) -> C {
+        self.get(from, to)
+    }
+}
+
+/// Cost matrix of an instance in the storage picked at run time.
+/// Hot loops match on it once per move and then run on the concrete `CostMatrix`,
+/// see `Operation::evaluate`.
+#[derive(Clone)]
+pub enum Matrix<C: Cost> {
+    Wide(CostMatrix<C>),
+    Narrow(CostMatrix<C, u16>),
+}
+
+impl<C: Cost> Matrix<C> {
+    pub fn dimension(&self) -> usize {
+        match self {
+            Matrix::Wide(matrix) => matrix.dimension(),
+            Matrix::Narrow(matrix) => matrix.dimension(),
+        }
+    }
+
+    pub fn is_narrow(&self) -> bool {
+        matches!(self, Matrix::Narrow(_))
+    }
+
+    #[inline(always)]
+    pub fn get(&self, from: usize, to: usize) -> C {
+        match self {
+            Matrix::Wide(matrix) => matrix.get(from, to),
+            Matrix::Narrow(matrix) => matrix.get(from, to),
+        }
+    }
+
+    pub fn row(&self, from: usize) -> impl Iterator<Item = C> + '_ {
+        (0..self.dimension()).map(move |to| self.get(from, to))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get_is_row_major() {
+        let matrix: CostMatrix<i32> = CostMatrix::new(3, (0..9).collect()).unwrap();
+        assert_eq!(matrix.get(0, 2), 2);
+        assert_eq!(matrix.get(2, 0), 6);
+        assert_eq!(matrix.row(1).collect::<Vec<_>>(), vec![3, 4, 5]);
+    }
+
+    #[test]
+    fn narrowed_keeps_values() {
+        let matrix = CostMatrix::new(2, vec![9999i64, 3, 0, 65535]).unwrap();
+        let narrow = matrix.narrowed().unwrap();
+        for i in 0..2 {
+            for j in 0..2 {
+                assert_eq!(matrix.get(i, j), narrow.get(i, j));
+            }
+        }
+    }
+
+    #[test]
+    fn narrowed_rejects_out_of_range() {
+        assert!(CostMatrix::new(2, vec![0i32, 65536, 1, 1])
+            .unwrap()
+            .narrowed()
+            .is_none());
+        assert!(CostMatrix::new(2, vec![0i32, -1, 1, 1])
+            .unwrap()
+            .narrowed()
+            .is_none());
+        assert!(CostMatrix::new(2, vec![0.0, 0.5, 1.0, 1.0])
+            .unwrap()
+            .narrowed()
+            .is_none());
+    }
+}
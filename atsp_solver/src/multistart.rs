@@ -0,0 +1,200 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+use crate::cost::Cost;
+use crate::search::SearchResult;
+
+/// Outcome of a single restart.
+pub struct RunRecord<C: Cost> {
+    pub seed: u64,
+    pub result: SearchResult<C>,
+    pub time_ns: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Summary {
+    pub min: f64,
+    pub mean: f64,
+    pub stdev: f64,
+    pub median: f64,
+}
+
+impl Summary {
+    pub fn from_values(values: &[f64]) -> Summary {
+        let n = values.len() as f64;
+        let mean = values.iter().sum::<f64>() / n;
+        let variance = if values.len() > 1 {
+            values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0)
+        } else {
+            0.0
+        };
+        let mut sorted = values.to_vec();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let mid = sorted.len() / 2;
+        let median = if sorted.len().is_multiple_of(2) {
+            (sorted[mid - 1] + sorted[mid]) / 2.0
+        } else {
+            sorted[mid]
+        };
+        Summary {
+            min: sorted[0],
+            mean,
+            stdev: variance.sqrt(),
+            median,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunStatistics {
+    pub runs: usize,
+    pub jobs: usize,
+    pub best_seed: u64,
+    pub cost: Summary,
+    #[serde(rename = "time")]
+    pub time_ns: Summary,
+    #[serde(rename = "wall_time")]
+    pub wall_time_ns: f64,
+}
+
+pub struct MultiStartResult<C: Cost> {
+    /// Restarts in seed order, independent of the number of jobs
+    pub runs: Vec<RunRecord<C>>,
+    pub best_run: usize,
+    pub stats: RunStatistics,
+}
+
+/// Seed of the given restart, derived from the base seed with SplitMix64
+/// so that neighbouring base seeds do not share restarts.
+pub fn derive_seed(base_seed: u64, run: usize) -> u64 {
+    let mut z = base_seed.wrapping_add(
+        (run as u64)
+            .wrapping_add(1)
+            .wrapping_mul(0x9E3779B97F4A7C15),
+    );
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^ (z >> 31)
+}
+
+/// Runs `restarts` independent searches on `jobs` worker threads.
+/// `run` receives the seed of the restart and must only depend on it.
+pub fn run_multistart<C, F>(restarts: usize, jobs: usize, seed: u64, run: F) -> MultiStartResult<C>
+where
+    C: Cost,
+    F: Fn(u64) -> SearchResult<C> + Sync,
+{
+    let (restarts, jobs) = (restarts.max(1), jobs.max(1));
+    let wall_start = Instant::now();
+    let timed_run = |idx: usize| {
+        let seed = derive_seed(seed, idx);
+        let start = Instant::now();
+        let result = run(seed);
+        RunRecord {
+            seed,
+            result,
+            time_ns: start.elapsed().as_nanos() as f64,
+        }
+    };
+
+    let runs: Vec<RunRecord<C>> = if jobs == 1 {
+        (0..restarts).map(timed_run).collect()
+    } else {
+        let next = AtomicUsize::new(0);
+        let slots: Mutex<Vec<Option<RunRecord<C>>>> =
+            Mutex::new((0..restarts).map(|_| None).collect());
+        std::thread::scope(|scope| {
+            for _ in 0..jobs.min(restarts) {
+                scope.spawn(|| loop {
+                    let idx = next.fetch_add(1, Ordering::Relaxed);
+                    if idx >= restarts {
+                        break;
+                    }
+                    let record = timed_run(idx);
+                    slots.lock().unwrap()[idx] = Some(record);
+                });
+            }
+        });
+        slots
+            .into_inner()
+            .unwrap()
+            .into_iter()
+            .map(|r| r.expect("Restart did not finish"))
+            .collect()
+    };
+
+    let mut best_run = 0;
+    for (idx, record) in runs.iter().enumerate() {
+        if record.result.context.best_cost < runs[best_run].result.context.best_cost {
+            best_run = idx;
+        }
+    }
+    let costs: Vec<f64> = runs
+        .iter()
+        .map(|r| r.result.context.best_cost.to_f64())
+        .collect();
+    let times: Vec<f64> = runs.iter().map(|r| r.time_ns).collect();
+    let stats = RunStatistics {
+        runs: restarts,
+        jobs,
+        best_seed: runs[best_run].seed,
+        cost: Summary::from_values(&costs),
+        time_ns: Summary::from_values(&times),
+        wall_time_ns: wall_start.elapsed().as_nanos() as f64,
+    };
+
+    MultiStartResult {
+        runs,
+        best_run,
+        stats,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::atsp::ATSP;
+    use crate::explorers::SteepestSearchExplorer;
+    use crate::initializers::RandomInitializer;
+    use crate::search::SearchAlgorithm;
+
+    #[test]
+    fn summary_of_values() {
+        let summary = Summary::from_values(&[4.0, 1.0, 3.0, 2.0]);
+        assert_eq!(summary.min, 1.0);
+        assert_eq!(summary.mean, 2.5);
+        assert_eq!(summary.median, 2.5);
+        assert!((summary.stdev - 1.2909944).abs() < 1e-6);
+    }
+
+    #[test]
+    fn derived_seeds_are_distinct() {
+        let seeds: std::collections::HashSet<u64> = (0..100).map(|i| derive_seed(0, i)).collect();
+        assert_eq!(seeds.len(), 100);
+        assert_ne!(derive_seed(0, 1), derive_seed(1, 0));
+    }
+
+    #[test]
+    fn parallel_runs_match_sequential_runs() {
+        let atsp: ATSP<i32> = ATSP::read_from_file("../data/ALL_atsp/br17.atsp").unwrap();
+        let run = |seed: u64| {
+            let mut initializer = RandomInitializer::new(seed);
+            let mut explorer = SteepestSearchExplorer::new(seed, 0b11, 1);
+            SearchAlgorithm::new(&atsp, &mut initializer, &mut explorer, -1).run()
+        };
+        let sequential = run_multistart(6, 1, 7, run);
+        let parallel = run_multistart(6, 3, 7, run);
+        let costs = |r: &MultiStartResult<i32>| -> Vec<i32> {
+            r.runs
+                .iter()
+                .map(|run| run.result.context.best_cost)
+                .collect()
+        };
+        assert_eq!(costs(&sequential), costs(&parallel));
+        assert_eq!(sequential.best_run, parallel.best_run);
+        assert_eq!(sequential.stats.cost, parallel.stats.cost);
+    }
+}
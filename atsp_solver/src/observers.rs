@@ -0,0 +1,260 @@
+//! Callbacks on the progress of a search and the observers shipped with the solver.
+
+use std::io::Write;
+
+use crate::atsp::ATSP;
+use crate::cost::Cost;
+use crate::search::{Context, SearchResult};
+use crate::solution::Solution;
+use crate::utils;
+
+/// Hooks called by `SearchAlgorithm::run`. Every callback does nothing by default.
+pub trait Observer<C: Cost>: Send {
+    /// Called once the initial solution is built
+    fn on_start(&mut self, _instance: &ATSP<C>, _solution: &Solution, _ctx: &Context<C>) {}
+
+    fn on_iteration_end(&mut self, _solution: &Solution, _ctx: &Context<C>) {}
+
+    fn on_new_best(&mut self, _solution: &Solution, _ctx: &Context<C>) {}
+
+    /// Called when the explorer restarts from a new solution, see `Context::restarts`
+    fn on_restart(&mut self, _solution: &Solution, _ctx: &Context<C>) {}
+
+    fn on_finish(&mut self, _result: &SearchResult<C>) {}
+
+    /// Stops the search early, checked after every iteration
+    fn stop_condition(&self, _ctx: &Context<C>) -> bool {
+        false
+    }
+}
+
+/// Prints the new best costs to stderr, at most once per interval, and the final cost.
+pub struct ProgressPrinter {
+    label: String,
+    interval_ns: f64,
+    last_print: Option<f64>,
+}
+
+impl ProgressPrinter {
+    pub fn new(label: &str, interval_ns: f64) -> ProgressPrinter {
+        ProgressPrinter {
+            label: label.to_string(),
+            interval_ns,
+            last_print: None,
+        }
+    }
+
+    fn print<C: Cost>(&mut self, ctx: &Context<C>) {
+        let time = ctx.elapsed_ns();
+        self.last_print = Some(time);
+        eprintln!(
+            "{}{} | evaluations {} | iterations {} | restarts {} | best {} | current {}",
+            self.label,
+            utils::humanize_time(time),
+            ctx.evaluations,
+            ctx.iterations,
+            ctx.restarts,
+            ctx.best_cost,
+            ctx.current_cost
+        );
+    }
+}
+
+impl<C: Cost> Observer<C> for ProgressPrinter {
+    fn on_start(&mut self, _: &ATSP<C>, _: &Solution, ctx: &Context<C>) {
+        self.print(ctx);
+    }
+
+    fn on_new_best(&mut self, _: &Solution, ctx: &Context<C>) {
+        if self
+            .last_print
+            .is_none_or(|last| ctx.elapsed_ns() - last >= self.interval_ns)
+        {
+            self.print(ctx);
+        }
+    }
+
+    fn on_finish(&mut self, result: &SearchResult<C>) {
+        self.print(&result.context);
+    }
+}
+
+/// Columns of the rows written by `TraceWriter`.
+pub const TRACE_HEADER: &str = "seed,evaluation,iteration,time,best,current";
+
+/// Streams the start and every improvement of the search as CSV rows, so that the trace
+/// can be followed while the search runs. Searches may share a file: every row is written at once.
+pub struct TraceWriter<W: Write + Send> {
+    writer: W,
+    seed: u64,
+    failed: bool,
+}
+
+impl<W: Write + Send> TraceWriter<W> {
+    pub fn new(writer: W, seed: u64) -> TraceWriter<W> {
+        TraceWriter {
+            writer,
+            seed,
+            failed: false,
+        }
+    }
+
+    fn write<C: Cost>(&mut self, ctx: &Context<C>) {
+        if self.failed {
+            return;
+        }
+        let row = format!(
+            "{},{},{},{},{},{}\n",
+            self.seed,
+            ctx.evaluations,
+            ctx.iterations,
+            ctx.elapsed_ns(),
+            ctx.best_cost,
+            ctx.current_cost
+        );
+        if let Err(error) = self.writer.write_all(row.as_bytes()) {
+            // A search is not stopped by its trace, the failure is reported once
+            eprintln!("Writing the trace failed: {}", error);
+            self.failed = true;
+        }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<C: Cost, W: Write + Send> Observer<C> for TraceWriter<W> {
+    fn on_start(&mut self, _: &ATSP<C>, _: &Solution, ctx: &Context<C>) {
+        self.write(ctx);
+    }
+
+    fn on_new_best(&mut self, _: &Solution, ctx: &Context<C>) {
+        self.write(ctx);
+    }
+
+    fn on_finish(&mut self, _: &SearchResult<C>) {
+        if !self.failed {
+            self.failed = self.writer.flush().is_err();
+        }
+    }
+}
+
+/// Stops the search once the best cost reaches the target, such as the best known cost.
+pub struct TargetCost<C: Cost> {
+    target: C,
+}
+
+impl<C: Cost> TargetCost<C> {
+    pub fn new(target: C) -> TargetCost<C> {
+        TargetCost { target }
+    }
+}
+
+impl<C: Cost> Observer<C> for TargetCost<C> {
+    fn stop_condition(&self, ctx: &Context<C>) -> bool {
+        ctx.best_cost <= self.target
+    }
+}
+
+/// Stops the search after a number of evaluations without improving the best cost.
+pub struct StagnationLimit {
+    max_evaluations: u32,
+}
+
+impl StagnationLimit {
+    pub fn new(max_evaluations: u32) -> StagnationLimit {
+        StagnationLimit { max_evaluations }
+    }
+}
+
+impl<C: Cost> Observer<C> for StagnationLimit {
+    fn stop_condition(&self, ctx: &Context<C>) -> bool {
+        let last_improvement = ctx.evaluations_history.last().copied().unwrap_or(0);
+        ctx.evaluations - last_improvement >= self.max_evaluations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::explorers::SimulatedAnnealingExplorer;
+    use crate::initializers::RandomInitializer;
+    use crate::operator_selection::OperatorSelector;
+    use crate::search::SearchAlgorithm;
+
+    #[derive(Default)]
+    struct Counter {
+        starts: u32,
+        iterations: u32,
+        new_bests: u32,
+        finishes: u32,
+    }
+
+    impl Observer<i32> for Counter {
+        fn on_start(&mut self, _: &ATSP<i32>, _: &Solution, _: &Context<i32>) {
+            self.starts += 1;
+        }
+
+        fn on_iteration_end(&mut self, _: &Solution, ctx: &Context<i32>) {
+            self.iterations += 1;
+            assert_eq!(self.iterations, ctx.iterations);
+        }
+
+        fn on_new_best(&mut self, solution: &Solution, ctx: &Context<i32>) {
+            self.new_bests += 1;
+            assert_eq!(ctx.best_cost, ctx.current_cost);
+            assert_eq!(solution.order.len(), 34);
+        }
+
+        fn on_finish(&mut self, _: &SearchResult<i32>) {
+            self.finishes += 1;
+        }
+    }
+
+    #[test]
+    fn observers_follow_and_stop_the_search() {
+        let atsp: ATSP<i32> = ATSP::read_from_file("../data/ALL_atsp/ftv33.atsp").unwrap();
+        let mut initializer = RandomInitializer::new(1);
+        let mut explorer =
+            SimulatedAnnealingExplorer::new(1, OperatorSelector::uniform(0b11), 100.0, 0.95, 34);
+        let mut counter = Counter::default();
+        let mut target = TargetCost::new(2500);
+        let mut trace = TraceWriter::new(Vec::new(), 7);
+        let result = SearchAlgorithm::new(&atsp, &mut initializer, &mut explorer, -1)
+            .with_observer(&mut counter)
+            .with_observer(&mut target)
+            .with_observer(&mut trace)
+            .run();
+
+        let ctx = &result.context;
+        assert!(ctx.best_cost <= 2500);
+        // The search stops at the first improvement reaching the target
+        assert!(ctx.cost_history[ctx.cost_history.len() - 2] > 2500);
+        assert_eq!((counter.starts, counter.finishes), (1, 1));
+        assert_eq!(counter.iterations, ctx.iterations);
+        assert_eq!(counter.new_bests as usize, ctx.cost_history.len() - 1);
+
+        let trace = String::from_utf8(trace.into_inner()).unwrap();
+        let rows: Vec<&str> = trace.lines().collect();
+        assert_eq!(rows.len(), ctx.cost_history.len());
+        assert!(rows.iter().all(|row| row.starts_with("7,")));
+        assert!(rows
+            .last()
+            .unwrap()
+            .ends_with(&format!(",{0},{0}", ctx.best_cost)));
+    }
+
+    #[test]
+    fn stagnation_limit_counts_evaluations_since_the_last_improvement() {
+        let mut ctx = Context::new(100);
+        ctx.evaluations = 40;
+        ctx.current_cost = 90;
+        ctx.on_change_best();
+        ctx.evaluations = 139;
+        let limit = StagnationLimit::new(100);
+        assert!(!Observer::<i32>::stop_condition(&limit, &ctx));
+        ctx.evaluations = 140;
+        assert!(Observer::<i32>::stop_condition(&limit, &ctx));
+    }
+}
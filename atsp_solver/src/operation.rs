@@ -1,3 +1,5 @@
+use crate::cost::Cost;
+use crate::matrix::{ArcCosts, Matrix};
 use crate::solution::Solution;
 use crate::utils;
 use crate::{atsp, deltas};
@@ -5,16 +7,21 @@ use bitflags::bitflags;
 use rand::rngs::StdRng;
 use rand::Rng;
 
-const MAX_NODES: u16 = 2u16.pow(10) - 1;
+/// Largest number of cities a move can address
+pub const MAX_NODES: u16 = 2u16.pow(10) - 1;
 
 #[derive(Debug)]
 enum OperationType {
     NodeSwap,
     EdgeSwap,
+    #[allow(dead_code)]
     ThreeOpt, // not implemented
     Invalid,
 }
 
+/// Arc of the tour as `(from, to)` cities.
+pub type Arc = (u32, u32);
+
 #[derive(Debug)]
 pub struct Operation {
     op_type: OperationType,
@@ -24,6 +31,7 @@ pub struct Operation {
 }
 
 bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     pub struct OperationFlags: u32 {
         const NODE_SWAP = 0b01;
         const EDGE_SWAP = 0b10;
@@ -41,6 +49,10 @@ impl Operation {
         }
     }
 
+    pub fn node_swap(first_idx: u16, second_idx: u16) -> Operation {
+        Operation::new(OperationType::NodeSwap, first_idx, second_idx, 0)
+    }
+
     pub fn to_int(&self) -> u32 {
         // first two bits -> op_type
         // 10 bits -> first_idx
@@ -82,6 +94,16 @@ impl Operation {
         }
     }
 
+    /// Flag of the move type of the operation.
+    pub fn flag(&self) -> OperationFlags {
+        match self.op_type {
+            OperationType::NodeSwap => OperationFlags::NODE_SWAP,
+            OperationType::EdgeSwap => OperationFlags::EDGE_SWAP,
+            OperationType::ThreeOpt => OperationFlags::THREE_OPT,
+            OperationType::Invalid => panic!("Invalid operation type"),
+        }
+    }
+
     pub fn apply(&self, solution: &mut Solution) {
         match self.op_type {
             OperationType::NodeSwap => {
@@ -106,19 +128,104 @@ impl Operation {
         }
     }
 
-    pub fn evaluate(&self, solution: &Solution, instance: &atsp::ATSP) -> i32 {
+    /// Arcs `(from, to)` removed from and added to the tour by the move.
+    /// The arcs inside a reversed segment change direction, so they are both removed and added.
+    pub fn changed_arcs(&self, order: &[u32]) -> (Vec<Arc>, Vec<Arc>) {
+        (
+            self.arcs(order, false).collect(),
+            self.arcs(order, true).collect(),
+        )
+    }
+
+    /// Arcs added to the tour by the move, without allocating.
+    pub fn added_arcs<'a>(&self, order: &'a [u32]) -> impl Iterator<Item = Arc> + 'a {
+        self.arcs(order, true)
+    }
+
+    /// Arcs removed from the tour by the move, without allocating.
+    pub fn removed_arcs<'a>(&self, order: &'a [u32]) -> impl Iterator<Item = Arc> + 'a {
+        self.arcs(order, false)
+    }
+
+    fn arcs<'a>(&self, order: &'a [u32], added: bool) -> impl Iterator<Item = Arc> + 'a {
+        let n = order.len();
+        let (i, j) = (
+            self.first_idx.min(self.second_idx) as usize,
+            self.first_idx.max(self.second_idx) as usize,
+        );
+        // Start positions of the changed arcs, the reversed segment and the arcs around it
+        let (starts, segment, ends) = match self.op_type {
+            OperationType::NodeSwap => {
+                let starts = [(i + n - 1) % n, i, (j + n - 1) % n, j];
+                (Some(starts), 0..0, None)
+            }
+            // Same as `apply`, which leaves the tour unchanged
+            OperationType::EdgeSwap if j - i < 2 || j - i == n - 1 => (None, 0..0, None),
+            OperationType::EdgeSwap if added => (
+                None,
+                i + 1..j,
+                Some([(order[i], order[j]), (order[i + 1], order[(j + 1) % n])]),
+            ),
+            OperationType::EdgeSwap => (None, i..j + 1, None),
+            _ => panic!("Bad operation!"),
+        };
+        let at = move |k: usize| match k {
+            k if added && k == i => order[j],
+            k if added && k == j => order[i],
+            k => order[k],
+        };
+        let node_arcs = starts.into_iter().flat_map(move |starts| {
+            (0..starts.len())
+                .filter(move |&idx| !starts[..idx].contains(&starts[idx]))
+                .map(move |idx| (at(starts[idx]), at((starts[idx] + 1) % n)))
+        });
+        let segment_arcs = segment.map(move |k| {
+            if added {
+                (order[k + 1], order[k])
+            } else {
+                (order[k], order[(k + 1) % n])
+            }
+        });
+        node_arcs
+            .chain(segment_arcs)
+            .chain(ends.into_iter().flatten())
+    }
+
+    /// Cities placed at new positions by the move: the swapped cities
+    /// or the ends of the reversed segment.
+    pub fn moved_cities(&self, order: &[u32]) -> [u32; 2] {
+        let (i, j) = (
+            self.first_idx.min(self.second_idx) as usize,
+            self.first_idx.max(self.second_idx) as usize,
+        );
+        match self.op_type {
+            OperationType::NodeSwap => [order[i], order[j]],
+            OperationType::EdgeSwap => [order[(i + 1) % order.len()], order[j]],
+            _ => panic!("Bad operation!"),
+        }
+    }
+
+    pub fn evaluate<C: Cost>(&self, solution: &Solution, instance: &atsp::ATSP<C>) -> C {
+        match &instance.matrix {
+            Matrix::Wide(matrix) => self.evaluate_with(solution, matrix),
+            Matrix::Narrow(matrix) => self.evaluate_with(solution, matrix),
+        }
+    }
+
+    /// Evaluates the move on arbitrary arc costs, such as penalized ones.
+    pub fn evaluate_with<C: Cost, M: ArcCosts<C>>(&self, solution: &Solution, costs: &M) -> C {
         match self.op_type {
             OperationType::NodeSwap => deltas::get_node_swap_delta(
                 &solution.order,
                 self.first_idx as usize,
                 self.second_idx as usize,
-                &instance.matrix,
+                costs,
             ),
             OperationType::EdgeSwap => deltas::get_edge_swap_delta(
                 &solution.order,
                 self.first_idx as usize,
                 self.second_idx as usize,
-                &instance.matrix,
+                costs,
             ),
             _ => panic!("Bad operation!"),
         }
@@ -338,6 +445,40 @@ mod tests {
         assert_eq!(sol.order, vec![0, 2, 1, 3, 4, 5, 6, 7, 8, 9]);
     }
 
+    #[test]
+    fn changed_arcs_match_the_applied_move() {
+        let order = vec![0, 1, 2, 3, 4, 5, 6, 7];
+        let arcs = |order: &[u32]| -> Vec<Arc> {
+            (0..order.len())
+                .map(|k| (order[k], order[(k + 1) % order.len()]))
+                .collect()
+        };
+        for op in [
+            Operation::new(OperationType::NodeSwap, 2, 5, 0),
+            Operation::new(OperationType::NodeSwap, 4, 3, 0),
+            Operation::new(OperationType::NodeSwap, 0, 7, 0),
+            Operation::new(OperationType::EdgeSwap, 1, 4, 0),
+            Operation::new(OperationType::EdgeSwap, 5, 7, 0),
+            Operation::new(OperationType::EdgeSwap, 2, 3, 0),
+            Operation::new(OperationType::EdgeSwap, 0, 7, 0),
+        ] {
+            let mut sol = Solution::new(&order).unwrap();
+            op.apply(&mut sol);
+            let (before, after) = (arcs(&order), arcs(&sol.order));
+            let (mut removed, mut added) = op.changed_arcs(&order);
+            removed.sort_unstable();
+            added.sort_unstable();
+            let difference = |a: &[Arc], b: &[Arc]| -> Vec<Arc> {
+                let mut difference: Vec<Arc> =
+                    a.iter().copied().filter(|arc| !b.contains(arc)).collect();
+                difference.sort_unstable();
+                difference
+            };
+            assert_eq!(removed, difference(&before, &after), "{:?}", op);
+            assert_eq!(added, difference(&after, &before), "{:?}", op);
+        }
+    }
+
     #[test]
     fn neighborhood_node_type_iterator_correct_size() {
         let it = NeighborhoodIterator::new(10, 0b01);
@@ -0,0 +1,254 @@
+use rand::rngs::StdRng;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::cost::Cost;
+use crate::operation::{self, Operation, OperationFlags};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Strategy {
+    /// Every enabled move type is equally likely
+    Uniform,
+    /// Probabilities proportional to the estimated qualities
+    ProbabilityMatching,
+    /// Probability of the best move type pursues its maximum, the others their minimum
+    AdaptivePursuit,
+    /// Upper confidence bound bandit over the success rates
+    Ucb,
+}
+
+/// Usage of a single move type. A use is successful when the move improves the cost.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OperatorStats {
+    pub name: String,
+    pub uses: u32,
+    pub successes: u32,
+}
+
+fn operator_name(operator: OperationFlags) -> &'static str {
+    if operator == OperationFlags::NODE_SWAP {
+        "node-swap"
+    } else if operator == OperationFlags::EDGE_SWAP {
+        "edge-swap"
+    } else {
+        "three-opt"
+    }
+}
+
+/// Chooses the type of sampled moves and learns from their cost changes.
+pub struct OperatorSelector {
+    strategy: Strategy,
+    op_flags: u32,
+    operators: Vec<OperationFlags>,
+    quality: Vec<f64>,
+    probabilities: Vec<f64>,
+    stats: Vec<OperatorStats>,
+    min_probability: f64,
+    adaptation_rate: f64,
+    learning_rate: f64,
+    exploration: f64,
+}
+
+impl OperatorSelector {
+    pub fn new(
+        strategy: Strategy,
+        op_flags: u32,
+        min_probability: f64,
+        adaptation_rate: f64,
+        learning_rate: f64,
+        exploration: f64,
+    ) -> OperatorSelector {
+        let operators: Vec<OperationFlags> = OperationFlags::from_bits(op_flags)
+            .expect("Invalid operation flags")
+            .iter()
+            .collect();
+        let num_operators = operators.len();
+        OperatorSelector {
+            strategy,
+            op_flags,
+            stats: operators
+                .iter()
+                .map(|&operator| OperatorStats {
+                    name: operator_name(operator).to_string(),
+                    uses: 0,
+                    successes: 0,
+                })
+                .collect(),
+            operators,
+            quality: vec![1.0; num_operators],
+            probabilities: vec![1.0 / num_operators as f64; num_operators],
+            min_probability: min_probability.clamp(0.0, 1.0 / num_operators as f64),
+            adaptation_rate,
+            learning_rate,
+            exploration,
+        }
+    }
+
+    /// The coin flip between all enabled move types.
+    pub fn uniform(op_flags: u32) -> OperatorSelector {
+        OperatorSelector::new(Strategy::Uniform, op_flags, 0.0, 0.0, 0.0, 0.0)
+    }
+
+    /// Samples a random move, returned together with the index of its type.
+    pub fn sample(&mut self, rng: &mut StdRng, num_nodes: u16) -> (usize, Operation) {
+        if self.strategy == Strategy::Uniform || self.operators.len() < 2 {
+            let op = operation::random_operation(rng, num_nodes, self.op_flags);
+            let operator = self
+                .operators
+                .iter()
+                .position(|&operator| operator == op.flag())
+                .unwrap();
+            return (operator, op);
+        }
+        let operator = match self.strategy {
+            Strategy::Ucb => self.upper_confidence_bound(),
+            _ => self.roulette(rng),
+        };
+        let op = operation::random_operation(rng, num_nodes, self.operators[operator].bits());
+        (operator, op)
+    }
+
+    pub fn stats(&self) -> &[OperatorStats] {
+        &self.stats
+    }
+
+    /// Rewards the move type when the move improved the cost.
+    pub fn feedback<C: Cost>(&mut self, operator: usize, cost_change: C) {
+        let reward = if cost_change < C::ZERO { 1.0 } else { 0.0 };
+        let stats = &mut self.stats[operator];
+        stats.uses += 1;
+        stats.successes += reward as u32;
+        match self.strategy {
+            Strategy::Uniform => {}
+            Strategy::ProbabilityMatching => {
+                self.adapt_quality(operator, reward);
+                let num_operators = self.operators.len() as f64;
+                let total_quality: f64 = self.quality.iter().sum();
+                for (probability, quality) in self.probabilities.iter_mut().zip(&self.quality) {
+                    *probability = if total_quality > 0.0 {
+                        self.min_probability
+                            + (1.0 - num_operators * self.min_probability) * quality / total_quality
+                    } else {
+                        1.0 / num_operators
+                    };
+                }
+            }
+            Strategy::AdaptivePursuit => {
+                self.adapt_quality(operator, reward);
+                let max_probability =
+                    1.0 - (self.operators.len() - 1) as f64 * self.min_probability;
+                let best = (0..self.quality.len())
+                    .max_by(|&a, &b| self.quality[a].partial_cmp(&self.quality[b]).unwrap())
+                    .unwrap();
+                for (i, probability) in self.probabilities.iter_mut().enumerate() {
+                    let target = if i == best {
+                        max_probability
+                    } else {
+                        self.min_probability
+                    };
+                    *probability += self.learning_rate * (target - *probability);
+                }
+            }
+            Strategy::Ucb => {
+                self.quality[operator] = stats.successes as f64 / stats.uses as f64;
+            }
+        }
+    }
+
+    fn adapt_quality(&mut self, operator: usize, reward: f64) {
+        self.quality[operator] += self.adaptation_rate * (reward - self.quality[operator]);
+    }
+
+    fn roulette(&self, rng: &mut StdRng) -> usize {
+        let mut threshold = rng.gen::<f64>() * self.probabilities.iter().sum::<f64>();
+        for (i, probability) in self.probabilities.iter().enumerate() {
+            if threshold < *probability {
+                return i;
+            }
+            threshold -= probability;
+        }
+        self.probabilities.len() - 1
+    }
+
+    fn upper_confidence_bound(&self) -> usize {
+        if let Some(untried) = self.stats.iter().position(|stats| stats.uses == 0) {
+            return untried;
+        }
+        let total_uses: f64 = self.stats.iter().map(|stats| stats.uses as f64).sum();
+        let bound = |i: usize| {
+            self.quality[i]
+                + self.exploration * (2.0 * total_uses.ln() / self.stats[i].uses as f64).sqrt()
+        };
+        (0..self.operators.len())
+            .max_by(|&a, &b| bound(a).partial_cmp(&bound(b)).unwrap())
+            .unwrap()
+    }
+}
+
+/// Adds up the statistics of several searches, matching move types by name.
+pub fn merge_stats(merged: &mut Vec<OperatorStats>, stats: &[OperatorStats]) {
+    for stat in stats {
+        match merged.iter_mut().find(|m| m.name == stat.name) {
+            Some(m) => {
+                m.uses += stat.uses;
+                m.successes += stat.successes;
+            }
+            None => merged.push(stat.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    /// Node swaps always improve, edge swaps never do.
+    fn learn(strategy: Strategy) -> OperatorSelector {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut selector = OperatorSelector::new(strategy, 0b11, 0.1, 0.2, 0.2, 0.5);
+        for _ in 0..2000 {
+            let (operator, op) = selector.sample(&mut rng, 10);
+            let cost_change = if op.flag() == OperationFlags::NODE_SWAP {
+                -1
+            } else {
+                1
+            };
+            selector.feedback(operator, cost_change);
+        }
+        selector
+    }
+
+    #[test]
+    fn uniform_selection_keeps_the_coin_flip() {
+        let selector = learn(Strategy::Uniform);
+        let stats = selector.stats;
+        assert_eq!(stats[0].name, "node-swap");
+        assert_eq!(stats[0].uses, stats[0].successes);
+        assert_eq!(stats[1].successes, 0);
+        assert!(stats[0].uses.abs_diff(stats[1].uses) < 200);
+    }
+
+    #[test]
+    fn adaptive_strategies_prefer_the_improving_move() {
+        for strategy in [
+            Strategy::ProbabilityMatching,
+            Strategy::AdaptivePursuit,
+            Strategy::Ucb,
+        ] {
+            let stats = learn(strategy).stats;
+            assert!(stats[0].uses > 4 * stats[1].uses, "{:?}", strategy);
+        }
+    }
+
+    #[test]
+    fn single_operator_is_always_chosen() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut selector = OperatorSelector::new(Strategy::Ucb, 0b10, 0.1, 0.2, 0.2, 0.5);
+        for _ in 0..10 {
+            let (operator, op) = selector.sample(&mut rng, 10);
+            assert_eq!(operator, 0);
+            assert_eq!(op.flag(), OperationFlags::EDGE_SWAP);
+        }
+    }
+}
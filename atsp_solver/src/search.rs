@@ -1,21 +1,60 @@
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
 use crate::atsp::ATSP;
+use crate::cost::Cost;
+use crate::initializers::RandomInitializer;
+use crate::observers::Observer;
+use crate::operator_selection::OperatorStats;
 use crate::solution::Solution;
 
+/// State of the search recorded every few evaluations, see `SearchBuilder::sample_interval`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(bound = "C: Cost")]
+pub struct Sample<C: Cost> {
+    pub evaluation: u32,
+    pub iteration: u32,
+    /// Nanoseconds since the start of the search
+    pub time: f64,
+    pub best: C,
+    pub current: C,
+}
+
+/// Markov chain of simulated annealing run at a single temperature.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChainStats {
+    /// Evaluations at the end of the chain
+    pub evaluation: u32,
+    pub temperature: f64,
+    /// Fraction of the sampled moves accepted during the chain
+    pub acceptance_rate: f64,
+}
+
 #[derive(Debug, Clone)]
-pub struct Context {
+pub struct Context<C: Cost> {
     pub iterations: u32,
     pub evaluations: u32,
     pub steps: u32,
-    pub initial_cost: i32,
-    pub current_cost: i32,
-    pub best_cost: i32,
+    pub initial_cost: C,
+    pub current_cost: C,
+    pub best_cost: C,
     pub iterations_without_improvement: u32,
+    /// Number of times the explorer restarted from a new solution
+    pub restarts: u32,
     pub evaluations_history: Vec<u32>,
-    pub cost_history: Vec<i32>,
+    pub iterations_history: Vec<u32>,
+    /// Nanoseconds since `started` at every improvement
+    pub time_history: Vec<f64>,
+    pub cost_history: Vec<C>,
+    pub operator_stats: Vec<OperatorStats>,
+    pub samples: Vec<Sample<C>>,
+    pub chains: Vec<ChainStats>,
+    pub started: Instant,
 }
 
-impl Context {
-    fn new(initial_cost: i32) -> Self {
+impl<C: Cost> Context<C> {
+    pub fn new(initial_cost: C) -> Self {
         Context {
             iterations: 0,
             evaluations: 0,
@@ -24,16 +63,46 @@ impl Context {
             current_cost: initial_cost,
             best_cost: initial_cost,
             iterations_without_improvement: 0,
+            restarts: 0,
             evaluations_history: vec![0],
+            iterations_history: vec![0],
+            time_history: vec![0.0],
             cost_history: vec![initial_cost],
+            operator_stats: Vec::new(),
+            samples: Vec::new(),
+            chains: Vec::new(),
+            started: Instant::now(),
         }
     }
 
+    /// Counts the evaluations spent on building the initial solution.
+    pub fn on_initialized(&mut self, evaluations: u32) {
+        self.evaluations += evaluations;
+        self.evaluations_history[0] = self.evaluations;
+        self.time_history[0] = self.elapsed_ns();
+    }
+
     pub fn on_change_best(&mut self) {
         self.iterations_without_improvement = 0;
         self.best_cost = self.current_cost;
         self.cost_history.push(self.best_cost);
         self.evaluations_history.push(self.evaluations);
+        self.iterations_history.push(self.iterations);
+        self.time_history.push(self.elapsed_ns());
+    }
+
+    pub fn record_sample(&mut self) {
+        self.samples.push(Sample {
+            evaluation: self.evaluations,
+            iteration: self.iterations,
+            time: self.elapsed_ns(),
+            best: self.best_cost,
+            current: self.current_cost,
+        });
+    }
+
+    pub fn elapsed_ns(&self) -> f64 {
+        self.started.elapsed().as_nanos() as f64
     }
 
     pub fn on_iteration_end(&mut self) {
@@ -42,43 +111,83 @@ impl Context {
     }
 }
 
-pub trait Initializer {
-    fn initialize(&mut self, instance: &ATSP) -> Solution;
+/// Outcome of a search: the best solution found and the context of the search.
+#[derive(Debug, Clone)]
+pub struct SearchResult<C: Cost> {
+    pub solution: Solution,
+    pub context: Context<C>,
+    /// Statistics of the explorer, see `Explorer::summary`
+    pub summary: Option<String>,
+}
+
+/// Initializers and explorers are `Send` so that searches can run on worker threads.
+pub trait Initializer<C: Cost>: Send {
+    fn initialize(&mut self, instance: &ATSP<C>) -> Solution;
+
+    /// Number of complete tours evaluated by the last `initialize` call to pick its result
+    fn evaluations(&self) -> u32 {
+        0
+    }
 }
 
-impl Initializer for Box<dyn Initializer> {
-    fn initialize(&mut self, instance: &ATSP) -> Solution {
+impl<C: Cost> Initializer<C> for Box<dyn Initializer<C>> {
+    fn initialize(&mut self, instance: &ATSP<C>) -> Solution {
         (**self).initialize(instance)
     }
+
+    fn evaluations(&self) -> u32 {
+        (**self).evaluations()
+    }
 }
 
-pub trait Explorer {
-    fn explore(&mut self, instance: &ATSP, solution: &mut Solution, context: &mut Context);
+pub trait Explorer<C: Cost>: Send {
+    fn explore(&mut self, instance: &ATSP<C>, solution: &mut Solution, context: &mut Context<C>);
+
+    fn stop_condition(&self, ctx: &Context<C>) -> bool;
+
+    /// Human readable statistics of the explorer, printed in verbose mode
+    fn summary(&self) -> Option<String> {
+        None
+    }
 
-    fn stop_condition(&self, ctx: &Context) -> bool;
+    /// Usage of the move types, copied into the context when the search ends
+    fn operator_stats(&self) -> Vec<OperatorStats> {
+        Vec::new()
+    }
 }
 
-impl Explorer for Box<dyn Explorer> {
-    fn explore(&mut self, instance: &ATSP, solution: &mut Solution, context: &mut Context) {
+impl<C: Cost> Explorer<C> for Box<dyn Explorer<C>> {
+    fn explore(&mut self, instance: &ATSP<C>, solution: &mut Solution, context: &mut Context<C>) {
         (**self).explore(instance, solution, context)
     }
 
-    fn stop_condition(&self, ctx: &Context) -> bool {
+    fn stop_condition(&self, ctx: &Context<C>) -> bool {
         (**self).stop_condition(ctx)
     }
+
+    fn summary(&self) -> Option<String> {
+        (**self).summary()
+    }
+
+    fn operator_stats(&self) -> Vec<OperatorStats> {
+        (**self).operator_stats()
+    }
 }
 
-pub struct SearchAlgorithm<'a, T: Initializer, U: Explorer> {
-    instance: &'a ATSP,
+pub struct SearchAlgorithm<'a, C: Cost, T: Initializer<C>, U: Explorer<C>> {
+    instance: &'a ATSP<C>,
     initializer: &'a mut T,
     explorer: &'a mut U,
     best_solution: Option<Solution>,
     max_time: i64,
+    max_evaluations: Option<u32>,
+    sample_interval: Option<u32>,
+    observers: Vec<&'a mut dyn Observer<C>>,
 }
 
-impl<'a, T: Initializer, U: Explorer> SearchAlgorithm<'a, T, U> {
+impl<'a, C: Cost, T: Initializer<C>, U: Explorer<C>> SearchAlgorithm<'a, C, T, U> {
     pub fn new(
-        instance: &'a ATSP,
+        instance: &'a ATSP<C>,
         initializer: &'a mut T,
         explorer: &'a mut U,
         max_time: i64,
@@ -89,10 +198,31 @@ impl<'a, T: Initializer, U: Explorer> SearchAlgorithm<'a, T, U> {
             explorer,
             best_solution: None,
             max_time,
+            max_evaluations: None,
+            sample_interval: None,
+            observers: Vec::new(),
         }
     }
 
-    pub fn run(&mut self) -> (Solution, Context) {
+    /// Stops the search once the explorer has used the given number of evaluations.
+    pub fn with_max_evaluations(mut self, max_evaluations: Option<u32>) -> Self {
+        self.max_evaluations = max_evaluations;
+        self
+    }
+
+    /// Records the current and best costs whenever the given number of evaluations has passed.
+    pub fn with_sample_interval(mut self, sample_interval: Option<u32>) -> Self {
+        self.sample_interval = sample_interval.filter(|&interval| interval > 0);
+        self
+    }
+
+    /// Notifies the observer of the progress of the search, observers are called in the order they were added.
+    pub fn with_observer(mut self, observer: &'a mut dyn Observer<C>) -> Self {
+        self.observers.push(observer);
+        self
+    }
+
+    pub fn run(&mut self) -> SearchResult<C> {
         let time_start = std::time::Instant::now();
         let mut solution = self.initializer.initialize(self.instance);
         let initial_cost = self.instance.cost_of_solution(&solution);
@@ -100,23 +230,165 @@ impl<'a, T: Initializer, U: Explorer> SearchAlgorithm<'a, T, U> {
         self.best_solution = Some(solution.clone());
 
         let mut ctx = Context::new(initial_cost);
+        // Improvements are timed from the start of the search, initialization included
+        ctx.started = time_start;
+        ctx.on_initialized(self.initializer.evaluations());
+        for observer in self.observers.iter_mut() {
+            observer.on_start(self.instance, &solution, &ctx);
+        }
         let mut stop_alg = false;
+        let mut next_sample = self.sample_interval.unwrap_or(u32::MAX);
 
         while !stop_alg {
+            let restarts = ctx.restarts;
             self.explorer
                 .explore(self.instance, &mut solution, &mut ctx);
 
+            if ctx.restarts > restarts {
+                for observer in self.observers.iter_mut() {
+                    observer.on_restart(&solution, &ctx);
+                }
+            }
             if ctx.current_cost < ctx.best_cost {
                 self.best_solution = Some(solution.clone());
                 ctx.on_change_best();
+                for observer in self.observers.iter_mut() {
+                    observer.on_new_best(&solution, &ctx);
+                }
+            }
+            if let Some(interval) = self.sample_interval {
+                // A single exploration may span several intervals, it is sampled once
+                if ctx.evaluations >= next_sample {
+                    ctx.record_sample();
+                    next_sample = (ctx.evaluations / interval + 1).saturating_mul(interval);
+                }
             }
 
             let time_break =
                 (self.max_time >= 0) && (time_start.elapsed().as_nanos() >= self.max_time as u128);
-            stop_alg = self.explorer.stop_condition(&ctx) || time_break;
+            let evaluations_break = self
+                .max_evaluations
+                .is_some_and(|max_evaluations| ctx.evaluations >= max_evaluations);
+            stop_alg = self.explorer.stop_condition(&ctx) || time_break || evaluations_break;
             ctx.on_iteration_end();
+            for observer in self.observers.iter_mut() {
+                observer.on_iteration_end(&solution, &ctx);
+                stop_alg |= observer.stop_condition(&ctx);
+            }
+        }
+
+        ctx.operator_stats = self.explorer.operator_stats();
+        let result = SearchResult {
+            solution: self.best_solution.clone().unwrap(),
+            context: ctx,
+            summary: self.explorer.summary(),
+        };
+        for observer in self.observers.iter_mut() {
+            observer.on_finish(&result);
+        }
+        result
+    }
+}
+
+/// Configures a search that owns its initializer and explorer.
+/// Without an initializer the search starts from a random tour.
+pub struct SearchBuilder<'a, C: Cost> {
+    instance: &'a ATSP<C>,
+    explorer: Box<dyn Explorer<C>>,
+    initializer: Option<Box<dyn Initializer<C>>>,
+    seed: u64,
+    max_time_ns: i64,
+    max_evaluations: Option<u32>,
+    sample_interval: Option<u32>,
+    observers: Vec<Box<dyn Observer<C> + 'a>>,
+}
+
+impl<'a, C: Cost> SearchBuilder<'a, C> {
+    pub fn new(instance: &'a ATSP<C>, explorer: Box<dyn Explorer<C>>) -> Self {
+        SearchBuilder {
+            instance,
+            explorer,
+            initializer: None,
+            seed: 0,
+            max_time_ns: -1,
+            max_evaluations: None,
+            sample_interval: None,
+            observers: Vec::new(),
+        }
+    }
+
+    pub fn initializer(mut self, initializer: Box<dyn Initializer<C>>) -> Self {
+        self.initializer = Some(initializer);
+        self
+    }
+
+    /// Seed of the default random initializer.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Time limit in nanoseconds, negative for none.
+    pub fn max_time_ns(mut self, max_time_ns: i64) -> Self {
+        self.max_time_ns = max_time_ns;
+        self
+    }
+
+    pub fn max_evaluations(mut self, max_evaluations: Option<u32>) -> Self {
+        self.max_evaluations = max_evaluations;
+        self
+    }
+
+    /// Number of evaluations between samples of the current cost, `None` or 0 for no samples.
+    pub fn sample_interval(mut self, sample_interval: Option<u32>) -> Self {
+        self.sample_interval = sample_interval;
+        self
+    }
+
+    pub fn observer(mut self, observer: Box<dyn Observer<C> + 'a>) -> Self {
+        self.observers.push(observer);
+        self
+    }
+
+    pub fn run(self) -> SearchResult<C> {
+        let mut explorer = self.explorer;
+        let mut initializer = self
+            .initializer
+            .unwrap_or_else(|| Box::new(RandomInitializer::new(self.seed)));
+        let mut observers = self.observers;
+        let mut search = SearchAlgorithm::new(
+            self.instance,
+            &mut initializer,
+            &mut explorer,
+            self.max_time_ns,
+        )
+        .with_max_evaluations(self.max_evaluations)
+        .with_sample_interval(self.sample_interval);
+        for observer in observers.iter_mut() {
+            search = search.with_observer(observer.as_mut());
         }
+        search.run()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::explorers::SteepestSearchExplorer;
 
-        (self.best_solution.clone().unwrap(), ctx)
+    #[test]
+    fn builder_defaults_to_a_seeded_random_start() {
+        let atsp: ATSP<i32> = ATSP::read_from_file("../data/ALL_atsp/br17.atsp").unwrap();
+        let run = || {
+            SearchBuilder::new(&atsp, Box::new(SteepestSearchExplorer::new(4, 0b11, 1)))
+                .seed(4)
+                .run()
+        };
+        let (first, second) = (run(), run());
+        assert_eq!(first.solution.order, second.solution.order);
+        assert_eq!(
+            atsp.cost_of_solution(&first.solution),
+            first.context.best_cost
+        );
     }
 }
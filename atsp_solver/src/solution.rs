@@ -1,4 +1,4 @@
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct Solution {
     pub dimension: usize,
     pub order: Vec<u32>,
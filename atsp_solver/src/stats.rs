@@ -0,0 +1,327 @@
+//! Nonparametric tests used to compare and race configurations on a set of instances.
+
+use std::f64::consts::PI;
+
+/// Ranks starting at 1, tied values share the mean of their ranks.
+pub fn ranks(values: &[f64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|&a, &b| values[a].partial_cmp(&values[b]).unwrap());
+    let mut ranks = vec![0.0; values.len()];
+    let mut start = 0;
+    while start < order.len() {
+        let mut end = start + 1;
+        while end < order.len() && values[order[end]] == values[order[start]] {
+            end += 1;
+        }
+        let rank = (start + end + 1) as f64 / 2.0;
+        for &idx in &order[start..end] {
+            ranks[idx] = rank;
+        }
+        start = end;
+    }
+    ranks
+}
+
+pub fn normal_cdf(x: f64) -> f64 {
+    0.5 * erfc(-x / std::f64::consts::SQRT_2)
+}
+
+/// Complementary error function with a relative error below 1.2e-7.
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let poly = -z * z - 1.26551223
+        + t * (1.00002368
+            + t * (0.37409196
+                + t * (0.09678418
+                    + t * (-0.18628806
+                        + t * (0.27886807
+                            + t * (-1.13520398
+                                + t * (1.48851587 + t * (-0.82215223 + t * 0.17087277))))))));
+    let result = t * poly.exp();
+    if x >= 0.0 {
+        result
+    } else {
+        2.0 - result
+    }
+}
+
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 6] = [
+        76.18009172947146,
+        -86.50532032941677,
+        24.01409824083091,
+        -1.231739572450155,
+        0.1208650973866179e-2,
+        -0.5395239384953e-5,
+    ];
+    let tmp = x + 5.5 - (x + 0.5) * (x + 5.5).ln();
+    let series: f64 = COEFFICIENTS
+        .iter()
+        .enumerate()
+        .map(|(i, c)| c / (x + 1.0 + i as f64))
+        .sum();
+    -tmp + (2.5066282746310005 * (1.000000000190015 + series) / x).ln()
+}
+
+/// Upper regularized incomplete gamma function Q(a, x).
+fn gamma_q(a: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 1.0;
+    }
+    let ln_prefix = a * x.ln() - x - ln_gamma(a);
+    if x < a + 1.0 {
+        let (mut term, mut sum, mut n) = (1.0 / a, 1.0 / a, a);
+        for _ in 0..500 {
+            n += 1.0;
+            term *= x / n;
+            sum += term;
+            if term.abs() < sum.abs() * 1e-15 {
+                break;
+            }
+        }
+        1.0 - sum * ln_prefix.exp()
+    } else {
+        // Continued fraction by the modified Lentz method
+        let tiny = 1e-300;
+        let mut b = x + 1.0 - a;
+        let mut c = 1.0 / tiny;
+        let mut d = 1.0 / b;
+        let mut h = d;
+        for i in 1..500 {
+            let an = -(i as f64) * (i as f64 - a);
+            b += 2.0;
+            d = an * d + b;
+            d = if d.abs() < tiny { tiny } else { d };
+            c = b + an / c;
+            c = if c.abs() < tiny { tiny } else { c };
+            d = 1.0 / d;
+            let delta = d * c;
+            h *= delta;
+            if (delta - 1.0).abs() < 1e-15 {
+                break;
+            }
+        }
+        ln_prefix.exp() * h
+    }
+}
+
+/// Probability that a chi-squared variable with `df` degrees of freedom exceeds `x`.
+pub fn chi_squared_sf(x: f64, df: f64) -> f64 {
+    gamma_q(df / 2.0, x / 2.0)
+}
+
+/// Friedman test of `k` treatments observed on blocks of `k` values, lower values rank first.
+#[derive(Debug, Clone)]
+pub struct Friedman {
+    pub mean_ranks: Vec<f64>,
+    pub statistic: f64,
+    pub p_value: f64,
+}
+
+pub fn friedman(blocks: &[Vec<f64>]) -> Friedman {
+    let n = blocks.len() as f64;
+    let k = blocks.first().map_or(0, |block| block.len());
+    let mut rank_sums = vec![0.0; k];
+    let mut tie_correction = 0.0;
+    for block in blocks {
+        let block_ranks = ranks(block);
+        for (sum, rank) in rank_sums.iter_mut().zip(&block_ranks) {
+            *sum += rank;
+        }
+        let mut sorted = block_ranks.clone();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        for group in sorted.chunk_by(|a, b| a == b) {
+            let t = group.len() as f64;
+            tie_correction += t * t * t - t;
+        }
+    }
+    let k_f = k as f64;
+    let mean_ranks: Vec<f64> = rank_sums.iter().map(|sum| sum / n).collect();
+    let denominator = 1.0 - tie_correction / (n * (k_f * k_f * k_f - k_f));
+    let statistic = if k < 2 || blocks.is_empty() || denominator <= 0.0 {
+        0.0
+    } else {
+        12.0 * n / (k_f * (k_f + 1.0))
+            * mean_ranks
+                .iter()
+                .map(|r| (r - (k_f + 1.0) / 2.0).powi(2))
+                .sum::<f64>()
+            / denominator
+    };
+    Friedman {
+        mean_ranks,
+        statistic,
+        p_value: if k < 2 {
+            1.0
+        } else {
+            chi_squared_sf(statistic, k_f - 1.0)
+        },
+    }
+}
+
+/// Probability that the range of `k` independent standard normal values is below `q`.
+fn normal_range_cdf(q: f64, k: usize) -> f64 {
+    if q <= 0.0 {
+        return 0.0;
+    }
+    let density = |z: f64| (-z * z / 2.0).exp() / (2.0 * PI).sqrt();
+    let integrand = |z: f64| density(z) * (normal_cdf(z) - normal_cdf(z - q)).powi(k as i32 - 1);
+    // Simpson's rule over the region where the normal density matters
+    let (low, high, steps) = (-8.0, 8.0 + q, 2000);
+    let h = (high - low) / steps as f64;
+    let mut sum = integrand(low) + integrand(high);
+    for i in 1..steps {
+        let weight = if i % 2 == 1 { 4.0 } else { 2.0 };
+        sum += weight * integrand(low + i as f64 * h);
+    }
+    (k as f64 * sum * h / 3.0).min(1.0)
+}
+
+/// Critical difference of the mean ranks of `k` treatments on `n` blocks in the Nemenyi test.
+pub fn critical_difference(k: usize, n: usize, alpha: f64) -> f64 {
+    if k < 2 || n == 0 {
+        return f64::INFINITY;
+    }
+    let (mut low, mut high) = (0.0, 20.0);
+    for _ in 0..60 {
+        let mid = (low + high) / 2.0;
+        if normal_range_cdf(mid, k) < 1.0 - alpha {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    let q_alpha = (low + high) / 2.0 / std::f64::consts::SQRT_2;
+    q_alpha * (k as f64 * (k as f64 + 1.0) / (6.0 * n as f64)).sqrt()
+}
+
+/// Wilcoxon signed-rank test of paired samples, zero differences are dropped.
+#[derive(Debug, Clone)]
+pub struct Wilcoxon {
+    /// Number of nonzero differences
+    pub n: usize,
+    /// Smaller of the rank sums of the positive and negative differences
+    pub statistic: f64,
+    /// Two-sided p-value, exact up to 50 differences and normal above
+    pub p_value: f64,
+}
+
+pub fn wilcoxon(x: &[f64], y: &[f64]) -> Wilcoxon {
+    let differences: Vec<f64> = x
+        .iter()
+        .zip(y)
+        .map(|(a, b)| a - b)
+        .filter(|d| *d != 0.0)
+        .collect();
+    let n = differences.len();
+    let abs_ranks = ranks(&differences.iter().map(|d| d.abs()).collect::<Vec<_>>());
+    let positive: f64 = abs_ranks
+        .iter()
+        .zip(&differences)
+        .filter(|(_, d)| **d > 0.0)
+        .fold(0.0, |sum, (r, _)| sum + r);
+    let total = (n * (n + 1)) as f64 / 2.0;
+    let statistic = positive.min(total - positive);
+    let p_value = if n == 0 {
+        1.0
+    } else if n <= 50 {
+        // Distribution of the doubled rank sum over every sign assignment, ties keep their ranks
+        let doubled: Vec<usize> = abs_ranks.iter().map(|r| (2.0 * r) as usize).collect();
+        let max_sum: usize = doubled.iter().sum();
+        let mut counts = vec![0.0f64; max_sum + 1];
+        counts[0] = 1.0;
+        for &rank in &doubled {
+            for sum in (rank..=max_sum).rev() {
+                counts[sum] += counts[sum - rank];
+            }
+        }
+        let at_most = (2.0 * statistic).round() as usize;
+        let tail: f64 = counts[..=at_most].iter().sum();
+        (2.0 * tail / 2f64.powi(n as i32)).min(1.0)
+    } else {
+        let mut variance = (n * (n + 1) * (2 * n + 1)) as f64 / 24.0;
+        let mut sorted = abs_ranks.clone();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        for group in sorted.chunk_by(|a, b| a == b) {
+            let t = group.len() as f64;
+            variance -= (t * t * t - t) / 48.0;
+        }
+        let z = (statistic - total / 2.0) / variance.sqrt();
+        (2.0 * normal_cdf(z)).min(1.0)
+    };
+    Wilcoxon {
+        n,
+        statistic,
+        p_value,
+    }
+}
+
+/// Holm adjustment of p-values for multiple comparisons, in the order of the input.
+pub fn holm(p_values: &[f64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..p_values.len()).collect();
+    order.sort_by(|&a, &b| p_values[a].partial_cmp(&p_values[b]).unwrap());
+    let mut adjusted = vec![0.0; p_values.len()];
+    let mut running_max: f64 = 0.0;
+    for (i, &idx) in order.iter().enumerate() {
+        let p = (p_values[idx] * (p_values.len() - i) as f64).min(1.0);
+        running_max = running_max.max(p);
+        adjusted[idx] = running_max;
+    }
+    adjusted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ties_share_their_rank() {
+        assert_eq!(ranks(&[3.0, 1.0, 3.0, 2.0]), vec![3.5, 1.0, 3.5, 2.0]);
+    }
+
+    #[test]
+    fn distribution_functions() {
+        assert!((normal_cdf(1.959964) - 0.975).abs() < 1e-6);
+        assert!((chi_squared_sf(3.841459, 1.0) - 0.05).abs() < 1e-6);
+        assert!((chi_squared_sf(18.307038, 10.0) - 0.05).abs() < 1e-6);
+    }
+
+    #[test]
+    fn friedman_detects_a_consistent_winner() {
+        let blocks: Vec<Vec<f64>> = (0..8)
+            .map(|i| vec![1.0 + i as f64, 2.0 + i as f64, 1.5 + i as f64])
+            .collect();
+        let test = friedman(&blocks);
+        assert_eq!(test.mean_ranks, vec![1.0, 3.0, 2.0]);
+        assert!((test.statistic - 16.0).abs() < 1e-9);
+        assert!(test.p_value < 0.001);
+    }
+
+    #[test]
+    fn critical_difference_matches_the_nemenyi_table() {
+        // q_0.05 of Demšar (2006) for 2, 4 and 10 classifiers
+        for (k, q) in [(2, 1.960), (4, 2.569), (10, 3.164)] {
+            let expected = q * (k as f64 * (k as f64 + 1.0) / 60.0).sqrt();
+            assert!((critical_difference(k, 10, 0.05) - expected).abs() < 2e-3);
+        }
+    }
+
+    #[test]
+    fn wilcoxon_matches_the_exact_distribution() {
+        // Every difference positive: only one of the 2^8 sign assignments is as extreme
+        let x: Vec<f64> = (1..=8).map(|i| i as f64 * 1.5).collect();
+        let y: Vec<f64> = (1..=8).map(|i| i as f64).collect();
+        let test = wilcoxon(&x, &y);
+        assert_eq!((test.n, test.statistic), (8, 0.0));
+        assert!((test.p_value - 2.0 / 256.0).abs() < 1e-12);
+        // Differences -1, 2, 3, 4, 5 with the ties of the zeros dropped
+        let test = wilcoxon(
+            &[0.0, 2.0, 3.0, 4.0, 5.0, 1.0],
+            &[1.0, 0.0, 0.0, 0.0, 0.0, 1.0],
+        );
+        assert_eq!((test.n, test.statistic), (5, 1.0));
+        assert!((test.p_value - 4.0 / 32.0).abs() < 1e-12);
+        assert_eq!(holm(&[0.01, 0.04, 0.03]), vec![0.03, 0.06, 0.06]);
+    }
+}
@@ -0,0 +1,405 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use rand::rngs::StdRng;
+use rand::Rng;
+
+use crate::atsp::ATSP;
+use crate::cost::Cost;
+use crate::explorers::TabuSearchExplorer;
+use crate::operation::{self, Operation};
+use crate::search::{Context, Explorer};
+use crate::solution::Solution;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TabuAttribute {
+    /// Recently removed arcs may not be added back
+    Arcs,
+    /// Recently moved cities may not be moved again
+    Cities,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Tenure {
+    Fixed(u32),
+    /// Drawn uniformly from `min..=max` for every recorded move
+    Random(u32, u32),
+    /// Starts in the middle of `min..=max`, grows when every candidate move is tabu
+    /// and shrinks when a new best solution is found, within `min..=max`
+    Reactive(u32, u32),
+    /// Starts in the middle of `min..=max` and is only changed from outside
+    /// through `grow_tenure` and `shrink_tenure`, within `min..=max`
+    Adaptive(u32, u32),
+}
+
+/// Tabu status of move attributes, stored as the iteration until which
+/// each arc or city stays tabu, so every lookup is O(1).
+pub struct TabuMemory {
+    attribute: TabuAttribute,
+    tenure: Tenure,
+    reactive_tenure: f64,
+    stamps: Vec<u32>,
+    dimension: usize,
+    iteration: u32,
+}
+
+impl TabuMemory {
+    pub fn new(attribute: TabuAttribute, tenure: Tenure) -> TabuMemory {
+        let reactive_tenure = match tenure {
+            Tenure::Reactive(min, max) | Tenure::Adaptive(min, max) => (min + max) as f64 / 2.0,
+            _ => 0.0,
+        };
+        TabuMemory {
+            attribute,
+            tenure,
+            reactive_tenure,
+            stamps: Vec::new(),
+            dimension: 0,
+            iteration: 0,
+        }
+    }
+
+    /// Iteration until which the move stays tabu. The move is tabu
+    /// while this is greater than the current iteration.
+    pub fn expiry(&self, op: &Operation, order: &[u32]) -> u32 {
+        if self.stamps.is_empty() {
+            return 0;
+        }
+        match self.attribute {
+            TabuAttribute::Arcs => op
+                .added_arcs(order)
+                .map(|(from, to)| self.stamps[from as usize * self.dimension + to as usize])
+                .max()
+                .unwrap_or(0),
+            TabuAttribute::Cities => op
+                .moved_cities(order)
+                .iter()
+                .map(|&city| self.stamps[city as usize])
+                .max()
+                .unwrap(),
+        }
+    }
+
+    pub fn is_tabu(&self, op: &Operation, order: &[u32]) -> bool {
+        self.expiry(op, order) > self.iteration
+    }
+
+    /// Makes the attributes of a move tabu. Must be called before the move is applied.
+    pub fn record(&mut self, op: &Operation, order: &[u32], rng: &mut StdRng) {
+        if self.stamps.is_empty() {
+            self.dimension = order.len();
+            self.stamps = match self.attribute {
+                TabuAttribute::Arcs => vec![0; self.dimension * self.dimension],
+                TabuAttribute::Cities => vec![0; self.dimension],
+            };
+        }
+        self.iteration += 1;
+        let until = self.iteration + self.draw_tenure(rng);
+        match self.attribute {
+            TabuAttribute::Arcs => {
+                for (from, to) in op.removed_arcs(order) {
+                    self.stamps[from as usize * self.dimension + to as usize] = until;
+                }
+            }
+            TabuAttribute::Cities => {
+                for city in op.moved_cities(order) {
+                    self.stamps[city as usize] = until;
+                }
+            }
+        }
+    }
+
+    /// Every candidate move was tabu.
+    pub fn on_blocked(&mut self) {
+        if let Tenure::Reactive(_, _) = self.tenure {
+            self.grow_tenure();
+        }
+    }
+
+    pub fn on_new_best(&mut self) {
+        if let Tenure::Reactive(_, _) = self.tenure {
+            self.shrink_tenure();
+        }
+    }
+
+    /// Lengthens a reactive or adaptive tenure, other tenures are left unchanged.
+    pub fn grow_tenure(&mut self) {
+        if let Tenure::Reactive(_, max) | Tenure::Adaptive(_, max) = self.tenure {
+            self.reactive_tenure = (self.reactive_tenure * 1.1 + 1.0).min(max as f64);
+        }
+    }
+
+    /// Shortens a reactive or adaptive tenure, other tenures are left unchanged.
+    pub fn shrink_tenure(&mut self) {
+        if let Tenure::Reactive(min, _) | Tenure::Adaptive(min, _) = self.tenure {
+            self.reactive_tenure = (self.reactive_tenure * 0.9).max(min as f64);
+        }
+    }
+
+    /// Current tenure, the midpoint of the range for random tenures.
+    pub fn tenure(&self) -> u32 {
+        match self.tenure {
+            Tenure::Fixed(tenure) => tenure,
+            Tenure::Random(min, max) => (min + max) / 2,
+            Tenure::Reactive(_, _) | Tenure::Adaptive(_, _) => self.reactive_tenure as u32,
+        }
+    }
+
+    fn draw_tenure(&self, rng: &mut StdRng) -> u32 {
+        match self.tenure {
+            Tenure::Random(min, max) => rng.gen_range(min..=max.max(min)),
+            _ => self.tenure(),
+        }
+    }
+}
+
+/// Hash of the tour read from city 0, the same for every rotation.
+fn tour_hash(order: &[u32]) -> u64 {
+    let start = order.iter().position(|&city| city == 0).unwrap_or(0);
+    let mut hasher = DefaultHasher::new();
+    for k in 0..order.len() {
+        order[(start + k) % order.len()].hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// Number of visits after which a tour is considered a chaotic trap and the search escapes.
+const MAX_VISITS: u32 = 3;
+
+/// Reactive tabu search: tabu search whose reactive tenure grows when visited tours
+/// repeat and shrinks when they do not. Instead of stopping on stagnation, it restarts
+/// from an elite solution perturbed towards rarely used arcs.
+pub struct ReactiveTabuExplorer<C: Cost> {
+    rng: StdRng,
+    tabu: TabuSearchExplorer<C>,
+    op_flags: u32,
+    stagnation: u32,
+    max_escapes: u32,
+    diversification: f64,
+    elite_size: usize,
+    elite: Vec<(u64, Solution, C)>,
+    visits: HashMap<u64, (u32, u32)>,
+    arc_frequencies: Vec<u32>,
+    cycle_length: f64,
+    last_tenure_change: u32,
+    last_escape: u32,
+    escapes_without_improvement: u32,
+    repetitions: u32,
+    escapes: u32,
+}
+
+impl<C: Cost> ReactiveTabuExplorer<C> {
+    pub fn new(
+        seed: u64,
+        tabu: TabuSearchExplorer<C>,
+        op_flags: u32,
+        stagnation: u32,
+        max_escapes: u32,
+        diversification: f64,
+        elite_size: usize,
+    ) -> ReactiveTabuExplorer<C> {
+        ReactiveTabuExplorer {
+            rng: rand::SeedableRng::seed_from_u64(seed),
+            tabu,
+            op_flags,
+            stagnation,
+            max_escapes,
+            diversification,
+            elite_size: elite_size.max(1),
+            elite: Vec::new(),
+            visits: HashMap::new(),
+            arc_frequencies: Vec::new(),
+            cycle_length: 0.0,
+            last_tenure_change: 0,
+            last_escape: 0,
+            escapes_without_improvement: 0,
+            repetitions: 0,
+            escapes: 0,
+        }
+    }
+
+    /// Records a visit of the tour. Returns true when the tour was visited too often.
+    fn react(&mut self, hash: u64, iteration: u32) -> bool {
+        let (visits, last_visit) = self.visits.entry(hash).or_insert((0, iteration));
+        let cycle = iteration - *last_visit;
+        *visits += 1;
+        *last_visit = iteration;
+        if *visits > 1 {
+            let visits = *visits;
+            self.repetitions += 1;
+            self.cycle_length = 0.9 * self.cycle_length + 0.1 * cycle as f64;
+            self.tabu.memory_mut().grow_tenure();
+            self.last_tenure_change = iteration;
+            return visits > MAX_VISITS;
+        }
+        if (iteration - self.last_tenure_change) as f64 > self.cycle_length.max(1.0) {
+            self.tabu.memory_mut().shrink_tenure();
+            self.last_tenure_change = iteration;
+        }
+        false
+    }
+
+    fn update_elite(&mut self, hash: u64, solution: &Solution, cost: C) {
+        if self.elite.iter().any(|(h, _, _)| *h == hash) {
+            return;
+        }
+        if self.elite.len() < self.elite_size {
+            self.elite.push((hash, solution.clone(), cost));
+        } else if let Some(worst) = self
+            .elite
+            .iter_mut()
+            .max_by(|a, b| a.2.partial_cmp(&b.2).unwrap())
+            .filter(|worst| cost < worst.2)
+        {
+            *worst = (hash, solution.clone(), cost);
+        }
+    }
+
+    /// Restarts from a random elite solution and applies random moves,
+    /// each chosen among a few samples as the one adding the least used arcs.
+    fn escape(&mut self, instance: &ATSP<C>, solution: &mut Solution, ctx: &mut Context<C>) {
+        let (_, elite, cost) = &self.elite[self.rng.gen_range(0..self.elite.len())];
+        *solution = elite.clone();
+        ctx.current_cost = *cost;
+        let n = instance.dimension;
+        let num_moves = (self.diversification * n as f64).ceil() as usize;
+        for _ in 0..num_moves {
+            let op = (0..5)
+                .map(|_| operation::random_operation(&mut self.rng, n as u16, self.op_flags))
+                .min_by_key(|op| {
+                    op.added_arcs(&solution.order)
+                        .map(|(from, to)| self.arc_frequencies[from as usize * n + to as usize])
+                        .sum::<u32>()
+                })
+                .unwrap();
+            ctx.current_cost += op.evaluate(solution, instance);
+            ctx.evaluations += 1;
+            ctx.steps += 1;
+            op.apply(solution);
+        }
+        self.visits.clear();
+        ctx.restarts += 1;
+        self.escapes += 1;
+        self.escapes_without_improvement += 1;
+        self.last_escape = ctx.iterations;
+    }
+}
+
+impl<C: Cost> Explorer<C> for ReactiveTabuExplorer<C> {
+    fn explore(&mut self, instance: &ATSP<C>, solution: &mut Solution, ctx: &mut Context<C>) {
+        let n = instance.dimension;
+        if self.arc_frequencies.is_empty() {
+            self.arc_frequencies = vec![0; n * n];
+            self.cycle_length = n as f64;
+        }
+        if ctx.iterations_without_improvement == 0 {
+            self.escapes_without_improvement = 0;
+        }
+
+        self.tabu.explore(instance, solution, ctx);
+        for k in 0..n {
+            let (from, to) = (solution.order[k], solution.order[(k + 1) % n]);
+            self.arc_frequencies[from as usize * n + to as usize] += 1;
+        }
+        let hash = tour_hash(&solution.order);
+        self.update_elite(hash, solution, ctx.current_cost);
+
+        let trapped = self.react(hash, ctx.iterations);
+        let stagnating = ctx.iterations_without_improvement >= self.stagnation
+            && ctx.iterations - self.last_escape >= self.stagnation;
+        if trapped || stagnating {
+            self.escape(instance, solution, ctx);
+        }
+    }
+
+    fn stop_condition(&self, _: &Context<C>) -> bool {
+        self.escapes_without_improvement >= self.max_escapes
+    }
+
+    fn summary(&self) -> Option<String> {
+        Some(format!(
+            "Repetitions: {}\nEscapes: {}\nTenure: {}\nMean cycle length: {:.2}\nElite solutions: {}",
+            self.repetitions,
+            self.escapes,
+            self.tabu.memory().tenure(),
+            self.cycle_length,
+            self.elite.len()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn removed_arcs_cannot_be_added_back() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut memory = TabuMemory::new(TabuAttribute::Arcs, Tenure::Fixed(2));
+        let mut order = vec![0, 1, 2, 3, 4, 5];
+        let op = Operation::from_int(1 << 30 | 1 << 20 | 4 << 10);
+        assert!(!memory.is_tabu(&op, &order));
+        memory.record(&op, &order, &mut rng);
+        let mut solution = crate::solution::Solution::new(&order).unwrap();
+        op.apply(&mut solution);
+        order = solution.order;
+
+        // Reversing the segment again restores the removed arcs
+        assert!(memory.is_tabu(&op, &order));
+        memory.record(&Operation::from_int(3 << 20 | 5 << 10), &order, &mut rng);
+        memory.record(&Operation::from_int(3 << 20 | 5 << 10), &order, &mut rng);
+        assert!(!memory.is_tabu(&op, &order));
+    }
+
+    #[test]
+    fn moved_cities_are_tabu() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut memory = TabuMemory::new(TabuAttribute::Cities, Tenure::Random(3, 5));
+        let order = vec![0, 1, 2, 3, 4, 5];
+        memory.record(&Operation::from_int(1 << 20 | 4 << 10), &order, &mut rng);
+        assert!(memory.is_tabu(&Operation::from_int(1 << 20 | 2 << 10), &order));
+        assert!(!memory.is_tabu(&Operation::from_int(2 << 20 | 3 << 10), &order));
+    }
+
+    #[test]
+    fn tour_hash_ignores_rotation() {
+        assert_eq!(tour_hash(&[2, 0, 1, 3]), tour_hash(&[0, 1, 3, 2]));
+        assert_ne!(tour_hash(&[0, 1, 2, 3]), tour_hash(&[0, 3, 2, 1]));
+    }
+
+    #[test]
+    fn reactive_tabu_escapes_instead_of_stopping() {
+        use crate::initializers::RandomInitializer;
+        use crate::search::{SearchAlgorithm, SearchResult};
+
+        let atsp: ATSP<i32> = ATSP::read_from_file("../data/ALL_atsp/ftv33.atsp").unwrap();
+        let memory = TabuMemory::new(TabuAttribute::Arcs, Tenure::Adaptive(3, 30));
+        let tabu = TabuSearchExplorer::new(3, 0b11, 0, 0.5, memory, 1);
+        let mut explorer = ReactiveTabuExplorer::new(3, tabu, 0b11, 50, 5, 0.1, 4);
+        let mut initializer = RandomInitializer::new(3);
+        let SearchResult {
+            solution,
+            context: ctx,
+            ..
+        } = SearchAlgorithm::new(&atsp, &mut initializer, &mut explorer, -1).run();
+
+        assert_eq!(atsp.cost_of_solution(&solution), ctx.best_cost);
+        assert!(explorer.escapes >= 5);
+        assert!(explorer.elite.len() <= 4);
+    }
+
+    #[test]
+    fn reactive_tenure_stays_in_range() {
+        let mut memory = TabuMemory::new(TabuAttribute::Arcs, Tenure::Reactive(5, 10));
+        for _ in 0..100 {
+            memory.on_blocked();
+        }
+        assert_eq!(memory.tenure(), 10);
+        for _ in 0..100 {
+            memory.on_new_best();
+        }
+        assert_eq!(memory.tenure(), 5);
+    }
+}
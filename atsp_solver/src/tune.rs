@@ -0,0 +1,495 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use evo_bio::best_known::{best_known_cost, relative_gap};
+use evo_bio::export::RunResult;
+use evo_bio::multistart::Summary;
+use evo_bio::{stats, utils};
+
+use crate::{args, bench};
+
+/// Values a parameter is sampled from: a list of values or a numeric range.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum ParameterRange {
+    Values(Vec<Value>),
+    Range {
+        min: f64,
+        max: f64,
+        /// Sample uniformly in the logarithm of the value
+        #[serde(default)]
+        log: bool,
+        #[serde(default)]
+        integer: bool,
+    },
+}
+
+impl ParameterRange {
+    fn validate(&self) -> Result<(), String> {
+        match self {
+            ParameterRange::Values(values) if values.is_empty() => {
+                Err("needs at least one value".to_string())
+            }
+            ParameterRange::Values(_) => Ok(()),
+            &ParameterRange::Range { min, max, log, .. } => {
+                if !(min.is_finite() && max.is_finite() && min <= max) {
+                    Err(format!("has an invalid range from {} to {}", min, max))
+                } else if log && min <= 0.0 {
+                    Err("must have a positive minimum on a log scale".to_string())
+                } else {
+                    Ok(())
+                }
+            }
+        }
+    }
+
+    fn sample(&self, rng: &mut StdRng) -> Value {
+        match self {
+            ParameterRange::Values(values) => values[rng.gen_range(0..values.len())].clone(),
+            &ParameterRange::Range {
+                min,
+                max,
+                log,
+                integer,
+            } => {
+                let u: f64 = rng.gen();
+                let value = if log {
+                    (min.ln() + u * (max.ln() - min.ln())).exp()
+                } else {
+                    min + u * (max - min)
+                };
+                if integer {
+                    json!(value.round() as i64)
+                } else {
+                    json!(value)
+                }
+            }
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Method {
+    /// Drops configurations whose Friedman rank is significantly worse than the best one
+    FRace,
+    /// Keeps the better half of the configurations on a doubling number of runs
+    SuccessiveHalving,
+}
+
+fn default_repeats() -> u64 {
+    1
+}
+
+fn default_elites() -> usize {
+    3
+}
+
+fn default_first_test() -> usize {
+    5
+}
+
+fn default_confidence() -> f64 {
+    0.95
+}
+
+/// Tuning problem: an algorithm, the parameters to sample and the training instances.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TuneConfig {
+    /// Algorithm, optionally followed by its initializer as in `tabu-search:nearest-neighbor`
+    pub algorithm: String,
+    pub instances_folder: String,
+    pub instances: Vec<String>,
+    /// Number of seeds every configuration is run with on every instance
+    #[serde(default = "default_repeats")]
+    pub repeats: u64,
+    #[serde(default)]
+    pub start_seed: u64,
+    /// Options shared by every configuration, such as the stopping criteria
+    #[serde(default)]
+    pub fixed_params: BTreeMap<String, Value>,
+    pub parameters: BTreeMap<String, ParameterRange>,
+    /// Number of sampled configurations
+    pub configurations: usize,
+    pub method: Method,
+    /// Number of configurations reported
+    #[serde(default = "default_elites")]
+    pub elites: usize,
+    /// Number of runs of every configuration before the first F-race test
+    #[serde(default = "default_first_test")]
+    pub first_test: usize,
+    #[serde(default = "default_confidence")]
+    pub confidence: f64,
+    /// Seed of the sampling of the configurations and the order of the runs
+    #[serde(default)]
+    pub seed: u64,
+}
+
+/// A sampled configuration and its gaps on the runs done so far.
+#[derive(Debug, Clone)]
+struct Candidate {
+    params: BTreeMap<String, Value>,
+    gaps: Vec<f64>,
+}
+
+impl Candidate {
+    fn mean_gap(&self) -> f64 {
+        self.gaps.iter().sum::<f64>() / self.gaps.len() as f64
+    }
+}
+
+/// A training run: an instance and the seed of the solver.
+struct Task {
+    instance: String,
+    seed: u64,
+    best_known: f64,
+}
+
+struct Tuner<'a> {
+    config: &'a TuneConfig,
+    tasks: Vec<Task>,
+    candidates: Vec<Candidate>,
+    jobs: usize,
+    runs: usize,
+}
+
+impl<'a> Tuner<'a> {
+    /// Samples the configurations and shuffles the runs so that early tests see different instances.
+    fn new(config: &'a TuneConfig, jobs: usize) -> Result<Tuner<'a>, String> {
+        if config.configurations == 0 || config.instances.is_empty() || config.repeats == 0 {
+            return Err("Tuning needs configurations, instances and repeats".to_string());
+        }
+        for (key, range) in config.parameters.iter() {
+            range
+                .validate()
+                .map_err(|e| format!("Parameter {} {}", key, e))?;
+        }
+        let mut rng = StdRng::seed_from_u64(config.seed);
+        let mut tasks = Vec::new();
+        for rep in 0..config.repeats {
+            for instance in config.instances.iter() {
+                let best_known = best_known_cost(instance)
+                    .ok_or_else(|| format!("No best known cost of {}", instance))?;
+                tasks.push(Task {
+                    instance: instance.clone(),
+                    seed: config.start_seed + rep,
+                    best_known: best_known as f64,
+                });
+            }
+        }
+        utils::shuffle(&mut tasks, &mut rng);
+        let candidates = (0..config.configurations)
+            .map(|_| Candidate {
+                params: config
+                    .parameters
+                    .iter()
+                    .map(|(key, range)| (key.clone(), range.sample(&mut rng)))
+                    .collect(),
+                gaps: Vec::new(),
+            })
+            .collect();
+        Ok(Tuner {
+            config,
+            tasks,
+            candidates,
+            jobs,
+            runs: 0,
+        })
+    }
+
+    fn gap(&self, candidate: usize, task: usize) -> Result<f64, String> {
+        let task = &self.tasks[task];
+        let mut values = serde_json::Map::new();
+        for (key, value) in self.config.fixed_params.iter() {
+            values.insert(key.clone(), value.clone());
+        }
+        for (key, value) in self.candidates[candidate].params.iter() {
+            values.insert(key.clone(), value.clone());
+        }
+        let instance =
+            Path::new(&self.config.instances_folder).join(format!("{}.atsp", task.instance));
+        let options = bench::solver_options(
+            &self.config.algorithm,
+            &instance,
+            task.seed,
+            args::config_args(values)?,
+        )?;
+        let result = crate::run_solver(&options).map_err(|e| e.to_string())?;
+        let result = RunResult::<f64>::from_json(&result).map_err(|e| e.to_string())?;
+        Ok(relative_gap(result.cost, task.best_known))
+    }
+
+    /// Runs the survivors on every task they have not been run on up to `tasks`.
+    fn evaluate(&mut self, survivors: &[usize], tasks: usize) -> Result<(), String> {
+        let pairs: Vec<(usize, usize)> = survivors
+            .iter()
+            .flat_map(|&candidate| {
+                (self.candidates[candidate].gaps.len()..tasks).map(move |task| (candidate, task))
+            })
+            .collect();
+        let tuner = &*self;
+        let gaps = utils::map_chunks(&pairs, self.jobs, |_, chunk| {
+            chunk
+                .iter()
+                .map(|&(candidate, task)| {
+                    tuner.gap(candidate, task).map_err(|e| {
+                        format!(
+                            "{:?} on {}: {}",
+                            tuner.candidates[candidate].params, task, e
+                        )
+                    })
+                })
+                .collect::<Vec<_>>()
+        });
+        for (&(candidate, _), gap) in pairs.iter().zip(gaps.into_iter().flatten()) {
+            self.candidates[candidate].gaps.push(gap?);
+        }
+        self.runs += pairs.len();
+        Ok(())
+    }
+
+    fn sort_by_mean_gap(&self, survivors: &mut [usize]) {
+        survivors.sort_by(|&a, &b| {
+            let (a, b) = (self.candidates[a].mean_gap(), self.candidates[b].mean_gap());
+            a.partial_cmp(&b).unwrap()
+        });
+    }
+
+    fn race(&mut self) -> Result<Vec<usize>, String> {
+        let elites = self.config.elites.max(1);
+        let mut survivors: Vec<usize> = (0..self.candidates.len()).collect();
+        // Every configuration is ranked on some runs, even when none has to be dropped
+        self.evaluate(
+            &survivors,
+            self.config.first_test.clamp(1, self.tasks.len()),
+        )?;
+        for task in 0..self.tasks.len() {
+            if survivors.len() <= elites {
+                break;
+            }
+            self.evaluate(&survivors, task + 1)?;
+            if task + 1 < self.config.first_test {
+                continue;
+            }
+            let blocks: Vec<Vec<f64>> = (0..=task)
+                .map(|t| {
+                    survivors
+                        .iter()
+                        .map(|&c| self.candidates[c].gaps[t])
+                        .collect()
+                })
+                .collect();
+            let test = stats::friedman(&blocks);
+            if test.p_value >= 1.0 - self.config.confidence {
+                continue;
+            }
+            let difference = stats::critical_difference(
+                survivors.len(),
+                blocks.len(),
+                1.0 - self.config.confidence,
+            );
+            let mut ranked: Vec<(f64, usize)> =
+                test.mean_ranks.iter().copied().zip(survivors).collect();
+            ranked.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+            let best_rank = ranked[0].0;
+            let before = ranked.len();
+            survivors = ranked
+                .iter()
+                .enumerate()
+                .filter(|&(idx, &(rank, _))| idx < elites || rank - best_rank <= difference)
+                .map(|(_, &(_, candidate))| candidate)
+                .collect();
+            eprintln!(
+                "Run {}: Friedman p = {:.4}, {} of {} configurations left",
+                task + 1,
+                test.p_value,
+                survivors.len(),
+                before
+            );
+        }
+        Ok(survivors)
+    }
+
+    fn halve(&mut self) -> Result<Vec<usize>, String> {
+        let elites = self.config.elites.max(1);
+        let mut survivors: Vec<usize> = (0..self.candidates.len()).collect();
+        let rounds = (self.candidates.len() as f64 / elites as f64)
+            .log2()
+            .ceil()
+            .max(1.0) as u32;
+        for round in 0..rounds {
+            let tasks = self.tasks.len().div_ceil(1 << (rounds - 1 - round)).max(1);
+            self.evaluate(&survivors, tasks)?;
+            self.sort_by_mean_gap(&mut survivors);
+            let before = survivors.len();
+            if round + 1 < rounds {
+                survivors.truncate(survivors.len().div_ceil(2).max(elites));
+            }
+            eprintln!(
+                "Round {} on {} runs: {} of {} configurations left",
+                round + 1,
+                tasks,
+                survivors.len(),
+                before
+            );
+        }
+        Ok(survivors)
+    }
+}
+
+/// Samples configurations of the algorithm and races them on the training instances.
+pub fn run(opt: &args::TuneOpt) -> Result<(), Box<dyn std::error::Error>> {
+    let config: TuneConfig = args::read_config_file(&opt.config)?;
+    let jobs = opt.jobs.map_or_else(
+        || std::thread::available_parallelism().map_or(1, |n| n.get()),
+        |jobs| jobs as usize,
+    );
+    let mut tuner = Tuner::new(&config, jobs)?;
+    let mut elites = match config.method {
+        Method::FRace => tuner.race()?,
+        Method::SuccessiveHalving => tuner.halve()?,
+    };
+    tuner.sort_by_mean_gap(&mut elites);
+    elites.truncate(config.elites.max(1));
+
+    println!(
+        "{} runs of {} configurations",
+        tuner.runs, config.configurations
+    );
+    println!(
+        "{:>4} {:>10} {:>10} {:>10} {:>5}  parameters",
+        "rank", "mean gap", "median", "best", "runs"
+    );
+    let mut report = Vec::new();
+    for (rank, &candidate) in elites.iter().enumerate() {
+        let candidate = &tuner.candidates[candidate];
+        let gap = Summary::from_values(&candidate.gaps);
+        let params: Vec<String> = candidate
+            .params
+            .iter()
+            .map(|(key, value)| format!("{} {}", key, value))
+            .collect();
+        println!(
+            "{:>4} {:>9.3}% {:>9.3}% {:>9.3}% {:>5}  {}",
+            rank + 1,
+            100.0 * gap.mean,
+            100.0 * gap.median,
+            100.0 * gap.min,
+            candidate.gaps.len(),
+            params.join(" ")
+        );
+        report.push(json!({
+            "params": candidate.params,
+            "runs": candidate.gaps.len(),
+            "gap": {"min": gap.min, "mean": gap.mean, "stdev": gap.stdev, "median": gap.median},
+        }));
+    }
+    if let Some(output) = &opt.output {
+        let report = json!({
+            "algorithm": config.algorithm,
+            "method": config.method,
+            "runs": tuner.runs,
+            "elites": report,
+        });
+        std::fs::write(output, serde_json::to_string_pretty(&report)?)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sampled_values_stay_in_their_range() {
+        let ranges: BTreeMap<String, ParameterRange> = serde_json::from_str(
+            r#"{"--sa-t0": {"min": 1, "max": 1000, "log": true},
+                "--tabu-patience": {"min": 2, "max": 20, "integer": true},
+                "--sa-chain-length": [0.5, 1.0]}"#,
+        )
+        .unwrap();
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..100 {
+            let t0 = ranges["--sa-t0"].sample(&mut rng).as_f64().unwrap();
+            assert!((1.0..=1000.0).contains(&t0));
+            let patience = ranges["--tabu-patience"].sample(&mut rng);
+            assert!((2..=20).contains(&patience.as_i64().unwrap()));
+            let length = ranges["--sa-chain-length"].sample(&mut rng);
+            assert!(length == json!(0.5) || length == json!(1.0));
+        }
+    }
+
+    #[test]
+    fn invalid_ranges_are_rejected() {
+        for range in [
+            r#"[]"#,
+            r#"{"min": 0, "max": 10, "log": true}"#,
+            r#"{"min": 2, "max": 1}"#,
+        ] {
+            let range: ParameterRange = serde_json::from_str(range).unwrap();
+            assert!(range.validate().is_err());
+        }
+    }
+
+    fn tune(method: &str, configurations: usize) -> Vec<Candidate> {
+        let config: TuneConfig = serde_json::from_str(&format!(
+            r#"{{
+                "algorithm": "tabu-search",
+                "instancesFolder": "../data/ALL_atsp",
+                "instances": ["br17", "ftv33"],
+                "repeats": 2,
+                "fixedParams": {{"--tabu-tenure": 0.2}},
+                "parameters": {{"--tabu-patience": [0, 20]}},
+                "configurations": {},
+                "method": "{}",
+                "elites": 2,
+                "firstTest": 2
+            }}"#,
+            configurations, method
+        ))
+        .unwrap();
+        let mut tuner = Tuner::new(&config, 2).unwrap();
+        let survivors = match config.method {
+            Method::FRace => tuner.race().unwrap(),
+            Method::SuccessiveHalving => tuner.halve().unwrap(),
+        };
+        survivors
+            .iter()
+            .map(|&c| tuner.candidates[c].clone())
+            .collect()
+    }
+
+    #[test]
+    fn patient_tabu_search_survives() {
+        for method in ["f-race", "successive-halving"] {
+            let survivors = tune(method, 6);
+            assert!(!survivors.is_empty());
+            assert!(survivors.len() <= 6);
+            assert!(
+                survivors
+                    .iter()
+                    .any(|candidate| candidate.params["--tabu-patience"] == json!(20)),
+                "{}",
+                method
+            );
+        }
+    }
+
+    #[test]
+    fn races_without_eliminations_still_run_every_configuration() {
+        for method in ["f-race", "successive-halving"] {
+            let survivors = tune(method, 2);
+            assert_eq!(survivors.len(), 2);
+            assert!(survivors.iter().all(|candidate| !candidate.gaps.is_empty()));
+            assert!(survivors
+                .iter()
+                .all(|candidate| candidate.mean_gap().is_finite()));
+        }
+    }
+}
@@ -1,6 +1,10 @@
+use crate::errors::MyError;
 use crate::solution::Solution;
 use rand::rngs::StdRng;
 use rand::Rng;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
 use std::time::{Duration, Instant};
 
 pub fn randomize_by_swaps(solution: &mut Solution, rng: &mut StdRng) {
@@ -13,7 +17,7 @@ pub fn generate_unique_duplet(max: usize, rng: &mut StdRng) -> (usize, usize) {
     (i, j)
 }
 
-pub fn shuffle<T: Into<u32>>(vector: &mut Vec<T>, rng: &mut StdRng) {
+pub fn shuffle<T>(vector: &mut Vec<T>, rng: &mut StdRng) {
     let size = vector.len();
     for i in 0..size {
         let j = rng.gen_range(i..size);
@@ -33,6 +37,117 @@ pub fn generate_decision(probability: f64, rng: &mut StdRng) -> bool {
     sampled < threshold
 }
 
+/// Splits `items` into up to `threads` contiguous chunks and maps each on its own thread.
+/// `f` receives the offset of the chunk in `items`; results are returned in chunk order.
+pub fn map_chunks<T, R, F>(items: &[T], threads: usize, f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(usize, &[T]) -> R + Sync,
+{
+    if threads <= 1 || items.len() < 2 {
+        return vec![f(0, items)];
+    }
+    let chunk_size = items.len().div_ceil(threads);
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = items
+            .chunks(chunk_size)
+            .enumerate()
+            .map(|(idx, chunk)| {
+                let f = &f;
+                scope.spawn(move || f(idx * chunk_size, chunk))
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|h| h.join().expect("Worker thread panicked"))
+            .collect()
+    })
+}
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// Worker threads kept alive between calls, so that a search spawns its threads once
+/// instead of on every neighborhood evaluation.
+pub struct WorkerPool {
+    jobs: Option<mpsc::Sender<Job>>,
+    workers: Vec<thread::JoinHandle<()>>,
+}
+
+impl WorkerPool {
+    pub fn new(threads: usize) -> WorkerPool {
+        let (jobs, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let workers = (0..threads)
+            .map(|_| {
+                let receiver = Arc::clone(&receiver);
+                thread::spawn(move || loop {
+                    let job = receiver.lock().unwrap().recv();
+                    match job {
+                        Ok(job) => job(),
+                        Err(_) => break,
+                    }
+                })
+            })
+            .collect();
+        WorkerPool {
+            jobs: Some(jobs),
+            workers,
+        }
+    }
+
+    /// Same as `map_chunks` on the pool threads. The items are shared through an `Arc`
+    /// since the workers outlive the call. Fails if `f` panics on any chunk.
+    pub fn map_chunks<T, R, F>(&self, items: &Arc<Vec<T>>, f: F) -> Result<Vec<R>, MyError>
+    where
+        T: Send + Sync + 'static,
+        R: Send + 'static,
+        F: Fn(usize, &[T]) -> R + Send + Sync + 'static,
+    {
+        if self.workers.len() <= 1 || items.len() < 2 {
+            return Ok(vec![f(0, items)]);
+        }
+        let chunk_size = items.len().div_ceil(self.workers.len());
+        let num_chunks = items.len().div_ceil(chunk_size);
+        let f = Arc::new(f);
+        let (results, receiver) = mpsc::channel();
+        for idx in 0..num_chunks {
+            let (items, f, results) = (Arc::clone(items), Arc::clone(&f), results.clone());
+            let job: Job = Box::new(move || {
+                let start = idx * chunk_size;
+                let end = (start + chunk_size).min(items.len());
+                // A panic is reported to the caller and leaves the worker running
+                let result = panic::catch_unwind(AssertUnwindSafe(|| f(start, &items[start..end])));
+                let _ = results.send((idx, result));
+            });
+            let sent = self.jobs.as_ref().map(|jobs| jobs.send(job));
+            if !matches!(sent, Some(Ok(()))) {
+                return Err(MyError::WorkerPanicked);
+            }
+        }
+        drop(results);
+        let mut chunks = Vec::with_capacity(num_chunks);
+        for (idx, result) in receiver.iter() {
+            chunks.push((idx, result.map_err(|_| MyError::WorkerPanicked)?));
+        }
+        if chunks.len() != num_chunks {
+            return Err(MyError::WorkerPanicked);
+        }
+        chunks.sort_by_key(|(idx, _)| *idx);
+        Ok(chunks.into_iter().map(|(_, result)| result).collect())
+    }
+}
+
+impl Drop for WorkerPool {
+    fn drop(&mut self) {
+        // Closing the channel stops the workers once they are idle
+        self.jobs.take();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
 pub fn measure_execution_time<F: FnMut()>(mut f: F) -> f64 {
     let mut total_duration = Duration::new(0, 0);
     let mut iterations = 0;
@@ -58,3 +173,41 @@ pub fn humanize_time(time: f64) -> String {
         format!("{:.2} s", time / 1_000_000_000.0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn map_chunks_keeps_chunk_order() {
+        let items: Vec<u32> = (0..10).collect();
+        let sums = map_chunks(&items, 3, |offset, chunk| {
+            (offset, chunk.iter().sum::<u32>())
+        });
+        assert_eq!(sums, vec![(0, 6), (4, 22), (8, 17)]);
+        assert_eq!(map_chunks(&items, 1, |_, chunk| chunk.len()), vec![10]);
+
+        let pool = WorkerPool::new(3);
+        let items = Arc::new(items);
+        for _ in 0..2 {
+            let sums = pool.map_chunks(&items, |offset, chunk| (offset, chunk.iter().sum::<u32>()));
+            assert_eq!(sums.unwrap(), vec![(0, 6), (4, 22), (8, 17)]);
+        }
+    }
+
+    #[test]
+    fn worker_panics_are_returned_as_errors() {
+        let pool = WorkerPool::new(2);
+        let items = Arc::new((0..10).collect::<Vec<u32>>());
+        let result = pool.map_chunks(&items, |offset, chunk| {
+            assert!(offset == 0, "second chunk fails");
+            chunk.len()
+        });
+        assert!(matches!(result, Err(MyError::WorkerPanicked)));
+        // The workers keep serving later calls
+        assert_eq!(
+            pool.map_chunks(&items, |_, chunk| chunk.len()).unwrap(),
+            vec![5, 5]
+        );
+    }
+}